@@ -1,4 +1,4 @@
-use crate::multipool_math::actions::adapter::MpAdapter;
+use crate::multipool_math::actions::adapter::{MpAdapter, MpApprovalAdapter};
 use ethers::prelude::*;
 use std::sync::Arc;
 
@@ -59,7 +59,8 @@ impl<M: Middleware, S: Signer> MpAdapter for EthersConnection<M, S> {
     ) -> Result<crate::multipool_math::MpContext, Self::Error> {
     }
     fn get_total_supply(&mut self, address: &str) -> Result<crate::num::num::Num, Self::Error> {}
-    fn get_current_block(&mut self) -> crate::num::num::Num {}
+    fn get_current_block(&mut self) -> Result<crate::num::num::Num, Self::Error> {}
+    fn get_current_timestamp(&mut self) -> Result<crate::num::num::Num, Self::Error> {}
     fn transact_mint(
         &mut self,
         params: &crate::multipool_math::actions::settings::MintTxnParams,
@@ -76,3 +77,24 @@ impl<M: Middleware, S: Signer> MpAdapter for EthersConnection<M, S> {
     ) -> Self::SwapTxnResult {
     }
 }
+
+impl<M: Middleware, S: Signer> MpApprovalAdapter for EthersConnection<M, S> {
+    type ApproveTxnResult = ();
+
+    fn get_allowance(
+        &mut self,
+        token: &str,
+        spender: &str,
+    ) -> Result<crate::num::num::Num, Self::Error> {
+    }
+    fn transact_approve(
+        &mut self,
+        params: &crate::multipool_math::actions::approval::ApproveTxnParams,
+    ) -> Self::ApproveTxnResult {
+    }
+    fn sign_permit(
+        &mut self,
+        params: &crate::multipool_math::actions::approval::PermitParams,
+    ) -> Result<crate::multipool_math::actions::approval::PermitSignature, Self::Error> {
+    }
+}
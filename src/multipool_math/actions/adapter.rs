@@ -1,9 +1,19 @@
 use crate::num::num::Num;
 use std::collections::HashMap;
 
+use super::approval::{ApproveTxnParams, PermitParams, PermitSignature};
+use super::builder::{ActionBuilder, Configured};
+use super::quote::TxnResult;
 use super::settings::{ActionSettings, BurnTxnParams, MintTxnParams, SwapTxnParams};
 use crate::multipool_math::{MpAsset, MpContext};
 
+/// Result of sending a quote through adapter `A`.
+pub type MpTxnResult<A> = TxnResult<
+    <A as MpAdapter>::MintTxnResult,
+    <A as MpAdapter>::BurnTxnResult,
+    <A as MpAdapter>::SwapTxnResult,
+>;
+
 pub trait MpAdapter: Sized {
     type Error;
     type MintTxnResult;
@@ -13,7 +23,8 @@ pub trait MpAdapter: Sized {
     fn get_context(&mut self, address: &str) -> Result<MpContext, Self::Error>;
     fn get_total_supply(&mut self, address: &str) -> Result<Num, Self::Error>;
     fn get_asset(&mut self, address: &str, asset: &str) -> Result<MpAsset, Self::Error>;
-    fn get_current_block(&mut self) -> Num;
+    fn get_current_block(&mut self) -> Result<Num, Self::Error>;
+    fn get_current_timestamp(&mut self) -> Result<Num, Self::Error>;
 
     fn transact_mint(&mut self, params: &MintTxnParams) -> Self::MintTxnResult;
     fn transact_burn(&mut self, params: &BurnTxnParams) -> Self::BurnTxnResult;
@@ -22,23 +33,46 @@ pub trait MpAdapter: Sized {
     fn configure(self) -> ActionSettings<Self> {
         ActionSettings::new(self)
     }
+
+    fn configure_typed(self) -> ActionBuilder<Self, Configured> {
+        ActionBuilder::new(self)
+    }
 }
 
+/// Adapter able to grant allowances, required by `ensure_allowance`.
+pub trait MpApprovalAdapter: MpAdapter {
+    type ApproveTxnResult;
+
+    /// Allowance of `spender` over `token` held by the adapter account.
+    fn get_allowance(&mut self, token: &str, spender: &str) -> Result<Num, Self::Error>;
+
+    fn transact_approve(&mut self, params: &ApproveTxnParams) -> Self::ApproveTxnResult;
+    fn sign_permit(&mut self, params: &PermitParams) -> Result<PermitSignature, Self::Error>;
+}
+
+#[derive(Clone, Debug)]
 pub struct MockedAdapter {
     pub assets: HashMap<String, MpAsset>,
     pub context: MpContext,
     pub total_supply: Num,
     pub current_block: Num,
+    pub current_timestamp: Num,
+    /// Allowances keyed by token and spender.
+    pub allowances: HashMap<(String, String), Num>,
 }
 
 impl MpAdapter for MockedAdapter {
-    type Error = ();
+    type Error = String;
     type MintTxnResult = MintTxnParams;
     type BurnTxnResult = BurnTxnParams;
     type SwapTxnResult = SwapTxnParams;
 
-    fn get_current_block(&mut self) -> Num {
-        self.current_block
+    fn get_current_block(&mut self) -> Result<Num, Self::Error> {
+        Ok(self.current_block)
+    }
+
+    fn get_current_timestamp(&mut self) -> Result<Num, Self::Error> {
+        Ok(self.current_timestamp)
     }
 
     fn get_context(&mut self, _address: &str) -> Result<MpContext, Self::Error> {
@@ -48,11 +82,10 @@ impl MpAdapter for MockedAdapter {
         Ok(self.total_supply)
     }
     fn get_asset(&mut self, _address: &str, asset: &str) -> Result<MpAsset, Self::Error> {
-        Ok(self
-            .assets
+        self.assets
             .get(asset)
-            .expect("no such asset in mock")
-            .to_owned())
+            .cloned()
+            .ok_or_else(|| format!("no such asset in mock: {asset}"))
     }
     fn transact_mint(&mut self, params: &MintTxnParams) -> Self::MintTxnResult {
         params.to_owned()
@@ -66,3 +99,26 @@ impl MpAdapter for MockedAdapter {
         params.to_owned()
     }
 }
+
+impl MpApprovalAdapter for MockedAdapter {
+    type ApproveTxnResult = ApproveTxnParams;
+
+    fn get_allowance(&mut self, token: &str, spender: &str) -> Result<Num, Self::Error> {
+        let key = (token.to_owned(), spender.to_owned());
+        Ok(self.allowances.get(&key).copied().unwrap_or(Num::ZERO))
+    }
+
+    fn transact_approve(&mut self, params: &ApproveTxnParams) -> Self::ApproveTxnResult {
+        let key = (params.token_address.clone(), params.spender_address.clone());
+        self.allowances.insert(key, params.amount);
+        params.to_owned()
+    }
+
+    fn sign_permit(&mut self, _params: &PermitParams) -> Result<PermitSignature, Self::Error> {
+        Ok(PermitSignature {
+            v: 27,
+            r: [1; 32],
+            s: [2; 32],
+        })
+    }
+}
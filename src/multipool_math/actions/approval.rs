@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::adapter::MpApprovalAdapter;
+use super::error::{required, ActionError};
+use super::quote::{Quote, QuoteOperation};
+use super::settings::ActionSettings;
+use super::{Deadline, DeadlineKind};
+use crate::num::num::Num;
+
+/// Lifetime of permits signed for quotes without a timestamp deadline.
+pub const PERMIT_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// How `ensure_allowance` grants the pool an allowance for asset in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Approval {
+    /// Sends an approve transaction before the trade.
+    #[default]
+    Transaction,
+    /// Signs an EIP-2612 permit submitted along with the trade.
+    Permit,
+}
+
+/// Approval of `amount` of the token to the pool pulling it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ApproveTxnParams {
+    pub token_address: String,
+    pub spender_address: String,
+    pub amount: Num,
+}
+
+/// EIP-2612 permit message. Owner and nonce are filled in by the adapter
+/// signing it.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct PermitParams {
+    pub token_address: String,
+    pub spender_address: String,
+    pub value: Num,
+    /// Unix timestamp in seconds.
+    pub deadline: Num,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct PermitSignature {
+    pub v: u8,
+    pub r: [u8; 32],
+    pub s: [u8; 32],
+}
+
+/// Signed permit bundled with mint and swap params.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Permit {
+    pub params: PermitParams,
+    pub signature: PermitSignature,
+}
+
+impl Quote {
+    /// Approval the quoted trade needs, `None` for burns which pull no
+    /// asset.
+    pub fn approval_params(&self) -> Option<ApproveTxnParams> {
+        if self.operation == QuoteOperation::Burn {
+            return None;
+        }
+        Some(ApproveTxnParams {
+            token_address: self.asset_in_address.clone()?,
+            spender_address: self.pool_address.clone(),
+            amount: self.amount_in_max,
+        })
+    }
+
+    /// Permit message for the approval, `now` being the current block
+    /// timestamp. Permits expire with timestamp deadlines of the quote and
+    /// `PERMIT_TTL` after `now` otherwise.
+    pub fn permit_params(&self, now: Num) -> Option<PermitParams> {
+        let approval = self.approval_params()?;
+        Some(PermitParams {
+            token_address: approval.token_address,
+            spender_address: approval.spender_address,
+            value: approval.amount,
+            deadline: match self.deadline_kind {
+                DeadlineKind::Timestamp => self.deadline,
+                _ => Deadline::Ttl(PERMIT_TTL).resolve(now).0,
+            },
+        })
+    }
+}
+
+impl<A> ActionSettings<A> {
+    /// Signs transactions with permits instead of sending approvals.
+    pub fn use_permit(mut self) -> Self {
+        self.approval = Approval::Permit;
+        self
+    }
+
+    /// Bundles `permit` with the calculated params.
+    pub(super) fn bundle_permit<E>(&mut self, permit: Permit) -> Result<(), ActionError<E>> {
+        let quote = required(self.quote.as_mut(), "quote")?;
+        quote.permit = Some(permit);
+        self.mint_params = quote.mint_params();
+        self.swap_params = quote.swap_params();
+        Ok(())
+    }
+}
+
+impl<A: MpApprovalAdapter> ActionSettings<A> {
+    /// Makes sure the pool may pull asset in for the calculated mint or
+    /// swap. Returns the result of the approve transaction if one was sent,
+    /// permits are bundled with the params instead. Burns and sufficient
+    /// allowances need neither.
+    pub fn ensure_allowance(
+        &mut self,
+    ) -> Result<Option<A::ApproveTxnResult>, ActionError<A::Error>> {
+        let quote = required(self.quote.as_ref(), "quote")?;
+        let Some(approval) = quote.approval_params() else {
+            return Ok(None);
+        };
+        let adapter = required(self.adapter.as_mut(), "adapter")?;
+        let allowance = adapter
+            .get_allowance(&approval.token_address, &approval.spender_address)
+            .map_err(ActionError::Adapter)?;
+        if allowance >= approval.amount {
+            return Ok(None);
+        }
+        match self.approval {
+            Approval::Transaction => Ok(Some(adapter.transact_approve(&approval))),
+            Approval::Permit => {
+                let now = adapter
+                    .get_current_timestamp()
+                    .map_err(ActionError::Adapter)?;
+                let permit = required(quote.permit_params(now), "permit params")?;
+                let signature = adapter.sign_permit(&permit).map_err(ActionError::Adapter)?;
+                self.bundle_permit(Permit {
+                    params: permit,
+                    signature,
+                })?;
+                Ok(None)
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use std::future::Future;
+use std::sync::{Mutex, PoisonError};
+
+use super::adapter::{MpAdapter, MpApprovalAdapter};
+use super::approval::{ApproveTxnParams, PermitParams, PermitSignature};
+use super::quote::TxnResult;
+use super::settings::{ActionSettings, BurnTxnParams, MintTxnParams, SwapTxnParams};
+use crate::multipool_math::{MpAsset, MpContext};
+use crate::num::num::Num;
+
+/// Result of sending a quote through adapter `A`.
+pub type AsyncMpTxnResult<A> = TxnResult<
+    <A as AsyncMpAdapter>::MintTxnResult,
+    <A as AsyncMpAdapter>::BurnTxnResult,
+    <A as AsyncMpAdapter>::SwapTxnResult,
+>;
+
+/// Asynchronous counterpart of `MpAdapter`. Methods take `&self`, so the
+/// fetches of a single action can run concurrently. Returned futures are
+/// `Send` so actions can be spawned on multi-threaded executors.
+pub trait AsyncMpAdapter: Sized {
+    type Error;
+    type MintTxnResult;
+    type BurnTxnResult;
+    type SwapTxnResult;
+
+    fn get_context(
+        &self,
+        address: &str,
+    ) -> impl Future<Output = Result<MpContext, Self::Error>> + Send;
+    fn get_total_supply(
+        &self,
+        address: &str,
+    ) -> impl Future<Output = Result<Num, Self::Error>> + Send;
+    fn get_asset(
+        &self,
+        address: &str,
+        asset: &str,
+    ) -> impl Future<Output = Result<MpAsset, Self::Error>> + Send;
+    fn get_current_block(&self) -> impl Future<Output = Result<Num, Self::Error>> + Send;
+    fn get_current_timestamp(&self) -> impl Future<Output = Result<Num, Self::Error>> + Send;
+
+    fn transact_mint(
+        &self,
+        params: &MintTxnParams,
+    ) -> impl Future<Output = Self::MintTxnResult> + Send;
+    fn transact_burn(
+        &self,
+        params: &BurnTxnParams,
+    ) -> impl Future<Output = Self::BurnTxnResult> + Send;
+    fn transact_swap(
+        &self,
+        params: &SwapTxnParams,
+    ) -> impl Future<Output = Self::SwapTxnResult> + Send;
+
+    fn configure_async(self) -> ActionSettings<Self> {
+        ActionSettings::new(self)
+    }
+}
+
+/// Asynchronous counterpart of `MpApprovalAdapter`.
+pub trait AsyncMpApprovalAdapter: AsyncMpAdapter {
+    type ApproveTxnResult;
+
+    fn get_allowance(
+        &self,
+        token: &str,
+        spender: &str,
+    ) -> impl Future<Output = Result<Num, Self::Error>> + Send;
+
+    fn transact_approve(
+        &self,
+        params: &ApproveTxnParams,
+    ) -> impl Future<Output = Self::ApproveTxnResult> + Send;
+    fn sign_permit(
+        &self,
+        params: &PermitParams,
+    ) -> impl Future<Output = Result<PermitSignature, Self::Error>> + Send;
+}
+
+/// Bridges any synchronous adapter. Calls are serialised by the mutex and
+/// resolve immediately.
+impl<A: MpAdapter + Send> AsyncMpAdapter for Mutex<A> {
+    type Error = A::Error;
+    type MintTxnResult = A::MintTxnResult;
+    type BurnTxnResult = A::BurnTxnResult;
+    type SwapTxnResult = A::SwapTxnResult;
+
+    async fn get_context(&self, address: &str) -> Result<MpContext, Self::Error> {
+        lock(self).get_context(address)
+    }
+
+    async fn get_total_supply(&self, address: &str) -> Result<Num, Self::Error> {
+        lock(self).get_total_supply(address)
+    }
+
+    async fn get_asset(&self, address: &str, asset: &str) -> Result<MpAsset, Self::Error> {
+        lock(self).get_asset(address, asset)
+    }
+
+    async fn get_current_block(&self) -> Result<Num, Self::Error> {
+        lock(self).get_current_block()
+    }
+
+    async fn get_current_timestamp(&self) -> Result<Num, Self::Error> {
+        lock(self).get_current_timestamp()
+    }
+
+    async fn transact_mint(&self, params: &MintTxnParams) -> Self::MintTxnResult {
+        lock(self).transact_mint(params)
+    }
+
+    async fn transact_burn(&self, params: &BurnTxnParams) -> Self::BurnTxnResult {
+        lock(self).transact_burn(params)
+    }
+
+    async fn transact_swap(&self, params: &SwapTxnParams) -> Self::SwapTxnResult {
+        lock(self).transact_swap(params)
+    }
+}
+
+impl<A: MpApprovalAdapter + Send> AsyncMpApprovalAdapter for Mutex<A> {
+    type ApproveTxnResult = A::ApproveTxnResult;
+
+    async fn get_allowance(&self, token: &str, spender: &str) -> Result<Num, Self::Error> {
+        lock(self).get_allowance(token, spender)
+    }
+
+    async fn transact_approve(&self, params: &ApproveTxnParams) -> Self::ApproveTxnResult {
+        lock(self).transact_approve(params)
+    }
+
+    async fn sign_permit(&self, params: &PermitParams) -> Result<PermitSignature, Self::Error> {
+        lock(self).sign_permit(params)
+    }
+}
+
+fn lock<A>(adapter: &Mutex<A>) -> std::sync::MutexGuard<'_, A> {
+    adapter.lock().unwrap_or_else(PoisonError::into_inner)
+}
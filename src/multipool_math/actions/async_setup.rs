@@ -0,0 +1,171 @@
+use futures::try_join;
+
+use super::approval::{Approval, Permit};
+use super::async_adapter::{AsyncMpAdapter, AsyncMpApprovalAdapter, AsyncMpTxnResult};
+use super::error::{required, ActionError};
+use super::quote::{Quote, QuoteOperation, TxnResult};
+use super::{settings::ActionSettings, DeadlineKind};
+use crate::multipool_math::MpAsset;
+use crate::num::num::Num;
+
+impl<A: AsyncMpAdapter> ActionSettings<A> {
+    /// Same as `fetch`, with all requests running concurrently.
+    pub async fn fetch_async(mut self) -> Result<Self, ActionError<A::Error>> {
+        let adapter = required(self.adapter.as_ref(), "adapter")?;
+        let pool_address = required(self.pool_address.as_ref(), "pool address")?;
+        if self.asset_in_address.is_none() && self.asset_out_address.is_none() {
+            return Err(ActionError::MissingField("asset address"));
+        }
+        let (context, total_supply, block, asset_in, asset_out) = try_join!(
+            adapter.get_context(pool_address),
+            adapter.get_total_supply(pool_address),
+            adapter.get_current_block(),
+            fetch_asset(adapter, pool_address, self.asset_in_address.as_deref()),
+            fetch_asset(adapter, pool_address, self.asset_out_address.as_deref()),
+        )
+        .map_err(ActionError::Adapter)?;
+        self.context = Some(context);
+        self.total_supply = Some(total_supply);
+        self.block = Some(block);
+        self.asset_in = asset_in;
+        self.asset_out = asset_out;
+        Ok(self)
+    }
+
+    pub async fn mint_async(self) -> Result<Self, ActionError<A::Error>> {
+        let deadline = self.deadline_async().await?;
+        self.compute_mint(deadline)
+    }
+
+    pub async fn burn_async(self) -> Result<Self, ActionError<A::Error>> {
+        let deadline = self.deadline_async().await?;
+        self.compute_burn(deadline)
+    }
+
+    pub async fn swap_async(self) -> Result<Self, ActionError<A::Error>> {
+        let deadline = self.deadline_async().await?;
+        self.compute_swap(deadline)
+    }
+
+    /// Same as `revalidate`, with all requests running concurrently.
+    pub async fn revalidate_async(self) -> Result<Self, ActionError<A::Error>> {
+        let quote = required(self.quote.clone(), "quote")?;
+        let settings = self.fetch_async().await?;
+        let now = match quote.deadline_kind {
+            DeadlineKind::Block => required(settings.block, "fetched block")?,
+            DeadlineKind::Timestamp => required(settings.adapter.as_ref(), "adapter")?
+                .get_current_timestamp()
+                .await
+                .map_err(ActionError::Adapter)?,
+            DeadlineKind::None => Num::ZERO,
+        };
+        settings.requote(&quote, now)
+    }
+
+    pub async fn send_mint_async(&self) -> Result<A::MintTxnResult, ActionError<A::Error>> {
+        let adapter = required(self.adapter.as_ref(), "adapter")?;
+        let params = required(self.mint_params.as_ref(), "mint params")?;
+        Ok(adapter.transact_mint(params).await)
+    }
+
+    pub async fn send_burn_async(&self) -> Result<A::BurnTxnResult, ActionError<A::Error>> {
+        let adapter = required(self.adapter.as_ref(), "adapter")?;
+        let params = required(self.burn_params.as_ref(), "burn params")?;
+        Ok(adapter.transact_burn(params).await)
+    }
+
+    pub async fn send_swap_async(&self) -> Result<A::SwapTxnResult, ActionError<A::Error>> {
+        let adapter = required(self.adapter.as_ref(), "adapter")?;
+        let params = required(self.swap_params.as_ref(), "swap params")?;
+        Ok(adapter.transact_swap(params).await)
+    }
+
+    pub async fn send_async(
+        &self,
+        quote: &Quote,
+    ) -> Result<AsyncMpTxnResult<A>, ActionError<A::Error>> {
+        let adapter = required(self.adapter.as_ref(), "adapter")?;
+        Ok(match quote.operation {
+            QuoteOperation::Mint => {
+                let params = required(quote.mint_params(), "mint params")?;
+                TxnResult::Mint(adapter.transact_mint(&params).await)
+            }
+            QuoteOperation::Burn => {
+                let params = required(quote.burn_params(), "burn params")?;
+                TxnResult::Burn(adapter.transact_burn(&params).await)
+            }
+            QuoteOperation::Swap => {
+                let params = required(quote.swap_params(), "swap params")?;
+                TxnResult::Swap(adapter.transact_swap(&params).await)
+            }
+        })
+    }
+
+    async fn deadline_async(&self) -> Result<(Num, DeadlineKind), ActionError<A::Error>> {
+        let Some(deadline) = self.deadline else {
+            return Ok((Num::ZERO, DeadlineKind::None));
+        };
+        let now = match deadline.clock() {
+            Some(DeadlineKind::Block) => required(self.adapter.as_ref(), "adapter")?
+                .get_current_block()
+                .await
+                .map_err(ActionError::Adapter)?,
+            Some(DeadlineKind::Timestamp) => required(self.adapter.as_ref(), "adapter")?
+                .get_current_timestamp()
+                .await
+                .map_err(ActionError::Adapter)?,
+            _ => Num::ZERO,
+        };
+        Ok(deadline.resolve(now))
+    }
+}
+
+impl<A: AsyncMpApprovalAdapter> ActionSettings<A> {
+    /// Same as `ensure_allowance`.
+    pub async fn ensure_allowance_async(
+        &mut self,
+    ) -> Result<Option<A::ApproveTxnResult>, ActionError<A::Error>> {
+        let quote = required(self.quote.as_ref(), "quote")?;
+        let Some(approval) = quote.approval_params() else {
+            return Ok(None);
+        };
+        let adapter = required(self.adapter.as_ref(), "adapter")?;
+        let allowance = adapter
+            .get_allowance(&approval.token_address, &approval.spender_address)
+            .await
+            .map_err(ActionError::Adapter)?;
+        if allowance >= approval.amount {
+            return Ok(None);
+        }
+        match self.approval {
+            Approval::Transaction => Ok(Some(adapter.transact_approve(&approval).await)),
+            Approval::Permit => {
+                let now = adapter
+                    .get_current_timestamp()
+                    .await
+                    .map_err(ActionError::Adapter)?;
+                let permit = required(quote.permit_params(now), "permit params")?;
+                let signature = adapter
+                    .sign_permit(&permit)
+                    .await
+                    .map_err(ActionError::Adapter)?;
+                self.bundle_permit(Permit {
+                    params: permit,
+                    signature,
+                })?;
+                Ok(None)
+            }
+        }
+    }
+}
+
+async fn fetch_asset<A: AsyncMpAdapter>(
+    adapter: &A,
+    pool_address: &str,
+    asset_address: Option<&str>,
+) -> Result<Option<MpAsset>, A::Error> {
+    match asset_address {
+        Some(address) => adapter.get_asset(pool_address, address).await.map(Some),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,333 @@
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use super::adapter::{MpAdapter, MpApprovalAdapter};
+use super::error::ActionError;
+use super::settings::ActionSettings;
+use crate::num::num::Num;
+
+pub struct Configured;
+pub struct PoolSet;
+pub struct AssetsSet<K>(PhantomData<K>);
+pub struct AmountSet<K>(PhantomData<K>);
+pub struct Fetched<K>(PhantomData<K>);
+pub struct Computed<K>(PhantomData<K>);
+
+/// Only asset in is selected, the action is a mint.
+pub struct AssetIn;
+/// Only asset out is selected, the action is a burn.
+pub struct AssetOut;
+/// Both assets are selected, the action is a swap.
+pub struct AssetPair;
+
+pub struct NoReceiver;
+pub struct Receiver;
+
+/// Stages before the calculation that still accept optional settings.
+pub trait Pending {}
+
+impl Pending for Configured {}
+impl Pending for PoolSet {}
+impl<K> Pending for AssetsSet<K> {}
+impl<K> Pending for AmountSet<K> {}
+impl<K> Pending for Fetched<K> {}
+
+/// Typestate counterpart of `ActionSettings`. Every stage is a distinct
+/// type, so skipping a required setting or sending the wrong transaction
+/// fails to compile:
+///
+/// `configure_typed()` → `pool()` → `asset_in()`/`asset_out()` →
+/// `amount_in()`/`amount_out()` → `fetch()` → `mint()`/`burn()`/`swap()` →
+/// `send()`
+///
+/// Selected assets decide the operation: only asset in mints, only asset
+/// out burns and both swap. `receiver()` may be called at any stage before
+/// the calculation, which isn't available without it.
+///
+/// ```
+/// # use arcanum_sdk::multipool_math::actions::{adapter::MpAdapter, error::ActionError};
+/// fn mint<A: MpAdapter>(adapter: A) -> Result<A::MintTxnResult, ActionError<A::Error>> {
+///     adapter
+///         .configure_typed()
+///         .pool("0x123")
+///         .asset_in("0xa")
+///         .amount_in("2")
+///         .receiver("0xME")
+///         .fetch()?
+///         .mint()?
+///         .send()
+/// }
+/// ```
+///
+/// Nothing is sent before the calculation:
+///
+/// ```compile_fail
+/// # use arcanum_sdk::multipool_math::actions::{adapter::MpAdapter, error::ActionError};
+/// fn mint<A: MpAdapter>(adapter: A) -> Result<A::MintTxnResult, ActionError<A::Error>> {
+///     adapter
+///         .configure_typed()
+///         .pool("0x123")
+///         .asset_in("0xa")
+///         .amount_in("2")
+///         .receiver("0xME")
+///         .fetch()?
+///         .send()
+/// }
+/// ```
+///
+/// Swaps need asset out:
+///
+/// ```compile_fail
+/// # use arcanum_sdk::multipool_math::actions::{adapter::MpAdapter, error::ActionError};
+/// fn swap<A: MpAdapter>(adapter: A) -> Result<A::SwapTxnResult, ActionError<A::Error>> {
+///     adapter
+///         .configure_typed()
+///         .pool("0x123")
+///         .asset_in("0xa")
+///         .amount_in("2")
+///         .receiver("0xME")
+///         .fetch()?
+///         .swap()?
+///         .send()
+/// }
+/// ```
+///
+/// Nothing is calculated without a receiver:
+///
+/// ```compile_fail
+/// # use arcanum_sdk::multipool_math::actions::{adapter::MpAdapter, error::ActionError};
+/// fn mint<A: MpAdapter>(adapter: A) -> Result<A::MintTxnResult, ActionError<A::Error>> {
+///     adapter
+///         .configure_typed()
+///         .pool("0x123")
+///         .asset_in("0xa")
+///         .amount_in("2")
+///         .fetch()?
+///         .mint()?
+///         .send()
+/// }
+/// ```
+pub struct ActionBuilder<A: MpAdapter, S, R = NoReceiver> {
+    settings: ActionSettings<A>,
+    stage: PhantomData<(S, R)>,
+}
+
+impl<A: MpAdapter, S, R> ActionBuilder<A, S, R> {
+    fn into_stage<T, Q>(self) -> ActionBuilder<A, T, Q> {
+        ActionBuilder {
+            settings: self.settings,
+            stage: PhantomData,
+        }
+    }
+
+    /// Settings the builder has collected so far, including calculated
+    /// params and price impact.
+    pub fn settings(&self) -> &ActionSettings<A> {
+        &self.settings
+    }
+
+    pub fn into_settings(self) -> ActionSettings<A> {
+        self.settings
+    }
+}
+
+impl<A: MpAdapter> ActionBuilder<A, Configured> {
+    pub fn new(adapter: A) -> Self {
+        Self {
+            settings: ActionSettings::new(adapter),
+            stage: PhantomData,
+        }
+    }
+}
+
+impl<A: MpAdapter, S: Pending> ActionBuilder<A, S, NoReceiver> {
+    pub fn receiver<V: Into<String>>(self, val: V) -> ActionBuilder<A, S, Receiver> {
+        let settings = self.settings.receiver(val);
+        ActionBuilder {
+            settings,
+            stage: PhantomData,
+        }
+    }
+}
+
+impl<A: MpAdapter, S: Pending, R> ActionBuilder<A, S, R> {
+    pub fn slippage_percent<V: Into<Num>>(mut self, val: V) -> Self {
+        self.settings = self.settings.slippage_percent(val);
+        self
+    }
+
+    pub fn slippage_bps<V: Into<Num>>(mut self, val: V) -> Self {
+        self.settings = self.settings.slippage_bps(val);
+        self
+    }
+
+    pub fn min_amount_out<V: Into<Num>>(mut self, val: V) -> Self {
+        self.settings = self.settings.min_amount_out(val);
+        self
+    }
+
+    pub fn max_amount_in<V: Into<Num>>(mut self, val: V) -> Self {
+        self.settings = self.settings.max_amount_in(val);
+        self
+    }
+
+    pub fn price_limit<V: Into<Num>>(mut self, val: V) -> Self {
+        self.settings = self.settings.price_limit(val);
+        self
+    }
+
+    pub fn until_block<V: Into<Num>>(mut self, val: V) -> Self {
+        self.settings = self.settings.until_block(val);
+        self
+    }
+
+    pub fn blocks_to_live<V: Into<Num>>(mut self, val: V) -> Self {
+        self.settings = self.settings.blocks_to_live(val);
+        self
+    }
+
+    pub fn until_timestamp<V: Into<Num>>(mut self, val: V) -> Self {
+        self.settings = self.settings.until_timestamp(val);
+        self
+    }
+
+    pub fn time_to_live(mut self, val: Duration) -> Self {
+        self.settings = self.settings.time_to_live(val);
+        self
+    }
+
+    pub fn use_permit(mut self) -> Self {
+        self.settings = self.settings.use_permit();
+        self
+    }
+}
+
+impl<A: MpAdapter, R> ActionBuilder<A, Configured, R> {
+    pub fn pool<V: Into<String>>(mut self, val: V) -> ActionBuilder<A, PoolSet, R> {
+        self.settings = self.settings.pool(val);
+        self.into_stage()
+    }
+}
+
+impl<A: MpAdapter, R> ActionBuilder<A, PoolSet, R> {
+    pub fn asset_in<V: Into<String>>(mut self, val: V) -> ActionBuilder<A, AssetsSet<AssetIn>, R> {
+        self.settings = self.settings.asset_in(val);
+        self.into_stage()
+    }
+
+    pub fn asset_out<V: Into<String>>(
+        mut self,
+        val: V,
+    ) -> ActionBuilder<A, AssetsSet<AssetOut>, R> {
+        self.settings = self.settings.asset_out(val);
+        self.into_stage()
+    }
+}
+
+impl<A: MpAdapter, R> ActionBuilder<A, AssetsSet<AssetIn>, R> {
+    pub fn asset_out<V: Into<String>>(
+        mut self,
+        val: V,
+    ) -> ActionBuilder<A, AssetsSet<AssetPair>, R> {
+        self.settings = self.settings.asset_out(val);
+        self.into_stage()
+    }
+}
+
+impl<A: MpAdapter, R> ActionBuilder<A, AssetsSet<AssetOut>, R> {
+    pub fn asset_in<V: Into<String>>(
+        mut self,
+        val: V,
+    ) -> ActionBuilder<A, AssetsSet<AssetPair>, R> {
+        self.settings = self.settings.asset_in(val);
+        self.into_stage()
+    }
+}
+
+impl<A: MpAdapter, K, R> ActionBuilder<A, AssetsSet<K>, R> {
+    pub fn amount_in<V: Into<Num>>(mut self, val: V) -> ActionBuilder<A, AmountSet<K>, R> {
+        self.settings = self.settings.amount_in(val);
+        self.into_stage()
+    }
+
+    pub fn amount_out<V: Into<Num>>(mut self, val: V) -> ActionBuilder<A, AmountSet<K>, R> {
+        self.settings = self.settings.amount_out(val);
+        self.into_stage()
+    }
+}
+
+impl<A: MpAdapter, K, R> ActionBuilder<A, AmountSet<K>, R> {
+    pub fn fetch(self) -> Result<ActionBuilder<A, Fetched<K>, R>, ActionError<A::Error>> {
+        Ok(ActionBuilder {
+            settings: self.settings.fetch()?,
+            stage: PhantomData,
+        })
+    }
+}
+
+impl<A: MpAdapter> ActionBuilder<A, Fetched<AssetIn>, Receiver> {
+    pub fn mint(
+        self,
+    ) -> Result<ActionBuilder<A, Computed<AssetIn>, Receiver>, ActionError<A::Error>> {
+        Ok(ActionBuilder {
+            settings: self.settings.mint()?,
+            stage: PhantomData,
+        })
+    }
+}
+
+impl<A: MpAdapter> ActionBuilder<A, Fetched<AssetOut>, Receiver> {
+    pub fn burn(
+        self,
+    ) -> Result<ActionBuilder<A, Computed<AssetOut>, Receiver>, ActionError<A::Error>> {
+        Ok(ActionBuilder {
+            settings: self.settings.burn()?,
+            stage: PhantomData,
+        })
+    }
+}
+
+impl<A: MpAdapter> ActionBuilder<A, Fetched<AssetPair>, Receiver> {
+    pub fn swap(
+        self,
+    ) -> Result<ActionBuilder<A, Computed<AssetPair>, Receiver>, ActionError<A::Error>> {
+        Ok(ActionBuilder {
+            settings: self.settings.swap()?,
+            stage: PhantomData,
+        })
+    }
+}
+
+impl<A: MpApprovalAdapter> ActionBuilder<A, Computed<AssetIn>, Receiver> {
+    pub fn ensure_allowance(
+        &mut self,
+    ) -> Result<Option<A::ApproveTxnResult>, ActionError<A::Error>> {
+        self.settings.ensure_allowance()
+    }
+}
+
+impl<A: MpAdapter> ActionBuilder<A, Computed<AssetIn>, Receiver> {
+    pub fn send(mut self) -> Result<A::MintTxnResult, ActionError<A::Error>> {
+        self.settings.send_mint()
+    }
+}
+
+impl<A: MpAdapter> ActionBuilder<A, Computed<AssetOut>, Receiver> {
+    pub fn send(mut self) -> Result<A::BurnTxnResult, ActionError<A::Error>> {
+        self.settings.send_burn()
+    }
+}
+
+impl<A: MpApprovalAdapter> ActionBuilder<A, Computed<AssetPair>, Receiver> {
+    pub fn ensure_allowance(
+        &mut self,
+    ) -> Result<Option<A::ApproveTxnResult>, ActionError<A::Error>> {
+        self.settings.ensure_allowance()
+    }
+}
+
+impl<A: MpAdapter> ActionBuilder<A, Computed<AssetPair>, Receiver> {
+    pub fn send(mut self) -> Result<A::SwapTxnResult, ActionError<A::Error>> {
+        self.settings.send_swap()
+    }
+}
@@ -1,220 +1,299 @@
-use super::settings::{BurnTxnParams, MintTxnParams, SwapTxnParams};
+use crate::multipool_math::{impact::PriceImpact, pool::MpPool};
 use crate::num::num::Num;
 
 use super::adapter::MpAdapter;
-use super::{settings::ActionSettings, Deadline, SidedQuantity, Slippage};
+use super::error::{required, ActionError};
+use super::quote::{Quote, QuoteFees, QuoteOperation};
+use super::{settings::ActionSettings, DeadlineKind, SidedQuantity};
 
 impl<A: MpAdapter> ActionSettings<A> {
-    pub fn mint(mut self) -> Self {
-        let adapter = self.adapter.as_mut().expect("adapter not set");
-        let mut context = self.context.as_ref().expect("context not set").clone();
-        let total_supply = self.total_supply.expect("total supply not set").clone();
-        let mut asset = self.asset_in.as_ref().expect("asset in not set").clone();
+    pub fn mint(mut self) -> Result<Self, ActionError<A::Error>> {
+        let deadline = self.deadline()?;
+        self.compute_mint(deadline)
+    }
+
+    pub fn burn(mut self) -> Result<Self, ActionError<A::Error>> {
+        let deadline = self.deadline()?;
+        self.compute_burn(deadline)
+    }
+
+    pub fn swap(mut self) -> Result<Self, ActionError<A::Error>> {
+        let deadline = self.deadline()?;
+        self.compute_swap(deadline)
+    }
+
+    /// Quote of the last calculated mint, burn or swap.
+    pub fn quote(&self) -> Result<Quote, ActionError<A::Error>> {
+        required(self.quote.clone(), "quote")
+    }
+
+    /// Refetches the pool state and recalculates the last quote. Fails if
+    /// the deadline of the quote has passed or the fresh amounts break its
+    /// bounds, which are kept for the recalculated params.
+    pub fn revalidate(self) -> Result<Self, ActionError<A::Error>> {
+        let quote = required(self.quote.clone(), "quote")?;
+        let mut settings = self.fetch()?;
+        let now = match quote.deadline_kind {
+            DeadlineKind::Block => required(settings.block, "fetched block")?,
+            DeadlineKind::Timestamp => required(settings.adapter.as_mut(), "adapter")?
+                .get_current_timestamp()
+                .map_err(ActionError::Adapter)?,
+            DeadlineKind::None => Num::ZERO,
+        };
+        settings.requote(&quote, now)
+    }
+
+    /// Block or timestamp the transaction expires at, zero if no deadline
+    /// is set.
+    fn deadline(&mut self) -> Result<(Num, DeadlineKind), ActionError<A::Error>> {
+        let Some(deadline) = self.deadline else {
+            return Ok((Num::ZERO, DeadlineKind::None));
+        };
+        let now = match deadline.clock() {
+            Some(DeadlineKind::Block) => required(self.adapter.as_mut(), "adapter")?
+                .get_current_block()
+                .map_err(ActionError::Adapter)?,
+            Some(DeadlineKind::Timestamp) => required(self.adapter.as_mut(), "adapter")?
+                .get_current_timestamp()
+                .map_err(ActionError::Adapter)?,
+            _ => Num::ZERO,
+        };
+        Ok(deadline.resolve(now))
+    }
+}
+
+impl<A> ActionSettings<A> {
+    pub(super) fn compute_mint<E>(
+        mut self,
+        deadline: (Num, DeadlineKind),
+    ) -> Result<Self, ActionError<E>> {
+        let context = required(self.context.clone(), "context")?;
+        let total_supply = required(self.total_supply, "total supply")?;
+        let asset = required(self.asset_in.clone(), "asset in")?;
+        let asset_in_address = required(self.asset_in_address.clone(), "asset in address")?;
+        let mut pool = MpPool::new(context, total_supply).with_asset(&asset_in_address, asset);
+        let before = pool.clone();
+        let amount_in;
         let shares;
-        let amount_in_max;
-        match self.quantity.expect("sending or receiving amount not set") {
-            SidedQuantity::QuantityIn(amount_in) => {
-                let amount_out = context
-                    .mint(&mut asset, amount_in)
-                    .expect("failed to calculate");
-                shares = amount_out * asset.price * total_supply / context.total_current_usd_amount;
-                amount_in_max = amount_in;
+        match required(self.quantity, "sending or receiving amount")? {
+            SidedQuantity::QuantityIn(amount) => {
+                amount_in = amount;
+                shares = pool.mint(&asset_in_address, amount)?;
             }
             SidedQuantity::QuantityOut(share) => {
                 shares = share;
-                let amount_out =
-                    share * context.total_current_usd_amount / asset.price / total_supply;
-                let amount_in = context
-                    .mint_rev(&mut asset, amount_out)
-                    .expect("failed to calculate");
-                amount_in_max = match self.slippage {
-                    Some(s) => match s {
-                        Slippage::Percent(p) => amount_in + amount_in * p,
-                    },
-                    None => amount_in,
-                }
+                amount_in = pool.mint_rev(&asset_in_address, share)?;
             }
         }
-        let deadline = match self.deadline {
-            Some(dl) => match dl {
-                Deadline::Block(b) => b,
-                Deadline::BlockInterval(i) => adapter.get_current_block() + i,
-            },
-            None => Num::ZERO,
-        };
-        self.mint_params = Some(MintTxnParams {
-            pool_address: self
-                .pool_address
-                .as_ref()
-                .expect("pool address not set")
-                .to_owned(),
-            asset_in_address: self
-                .asset_in_address
-                .as_ref()
-                .expect("asset in address not set")
-                .to_owned(),
+        let impact = PriceImpact::mint(&before, &asset_in_address, amount_in, shares)?;
+        let quote = Quote {
+            amount_in,
+            amount_out: shares,
             shares,
-            amount_in_max,
-            receiver_address: self
-                .receiver_address
-                .as_ref()
-                .expect("receiver address not set")
-                .to_owned(),
-            deadline,
-        });
-        self
+            amount_in_max: self.max_in(amount_in, shares)?,
+            amount_out_min: self.min_out(amount_in, shares)?,
+            ..self.base_quote(QuoteOperation::Mint, &before, &pool, impact, deadline)?
+        };
+        self.mint_params = quote.mint_params();
+        self.price_impact = Some(impact);
+        self.quote = Some(quote);
+        Ok(self)
     }
 
-    pub fn burn(mut self) -> Self {
-        let adapter = self.adapter.as_mut().expect("adapter not set");
-        let mut context = self.context.as_ref().expect("context not set").clone();
-        let total_supply = self.total_supply.expect("total supply not set").clone();
-        let mut asset = self.asset_out.as_ref().expect("asset out not set").clone();
+    pub(super) fn compute_burn<E>(
+        mut self,
+        deadline: (Num, DeadlineKind),
+    ) -> Result<Self, ActionError<E>> {
+        let context = required(self.context.clone(), "context")?;
+        let total_supply = required(self.total_supply, "total supply")?;
+        let asset = required(self.asset_out.clone(), "asset out")?;
+        let asset_out_address = required(self.asset_out_address.clone(), "asset out address")?;
+        let mut pool = MpPool::new(context, total_supply).with_asset(&asset_out_address, asset);
+        let before = pool.clone();
         let shares;
-        let amount_out_min;
-        match self.quantity.expect("sending or receiving amount not set") {
+        let amount_out;
+        match required(self.quantity, "sending or receiving amount")? {
             SidedQuantity::QuantityIn(share) => {
                 shares = share;
-                let amount_in =
-                    share * context.total_current_usd_amount / asset.price / total_supply;
-                let amount_out = context
-                    .burn(&mut asset, amount_in)
-                    .expect("failed to calculate");
-                amount_out_min = match self.slippage {
-                    Some(s) => match s {
-                        Slippage::Percent(p) => amount_out + amount_out * p,
-                    },
-                    None => amount_out,
-                }
+                amount_out = pool.burn(&asset_out_address, share)?;
             }
-            SidedQuantity::QuantityOut(amount_out) => {
-                let amount_in = context
-                    .burn_rev(&mut asset, amount_out)
-                    .expect("failed to calculate");
-                shares = amount_in * asset.price * total_supply / context.total_current_usd_amount;
-                amount_out_min = amount_out;
+            SidedQuantity::QuantityOut(amount) => {
+                amount_out = amount;
+                shares = pool.burn_rev(&asset_out_address, amount)?;
             }
         }
-        let deadline = match self.deadline {
-            Some(dl) => match dl {
-                Deadline::Block(b) => b,
-                Deadline::BlockInterval(i) => adapter.get_current_block() + i,
-            },
-            None => Num::ZERO,
-        };
-        self.burn_params = Some(BurnTxnParams {
-            pool_address: self
-                .pool_address
-                .as_ref()
-                .expect("pool address not set")
-                .to_owned(),
-            asset_out_address: self
-                .asset_out_address
-                .as_ref()
-                .expect("asset out address not set")
-                .to_owned(),
+        let impact = PriceImpact::burn(&before, &asset_out_address, shares, amount_out)?;
+        let quote = Quote {
+            amount_in: shares,
+            amount_out,
             shares,
-            amount_out_min,
-            receiver_address: self
-                .receiver_address
-                .as_ref()
-                .expect("receiver address not set")
-                .to_owned(),
-            deadline,
-        });
-        self
+            amount_in_max: self.max_in(shares, amount_out)?,
+            amount_out_min: self.min_out(shares, amount_out)?,
+            ..self.base_quote(QuoteOperation::Burn, &before, &pool, impact, deadline)?
+        };
+        self.burn_params = quote.burn_params();
+        self.price_impact = Some(impact);
+        self.quote = Some(quote);
+        Ok(self)
     }
 
-    pub fn swap(mut self) -> Self {
-        let adapter = self.adapter.as_mut().expect("adapter not set");
-        let mut context = self.context.as_ref().expect("context not set").clone();
-        let total_supply = self.total_supply.expect("total supply not set").clone();
-        let mut asset_out = self.asset_out.as_ref().expect("asset out not set").clone();
-        let mut asset_in = self.asset_in.as_ref().expect("asset in not set").clone();
+    pub(super) fn compute_swap<E>(
+        mut self,
+        deadline: (Num, DeadlineKind),
+    ) -> Result<Self, ActionError<E>> {
+        let context = required(self.context.clone(), "context")?;
+        let total_supply = required(self.total_supply, "total supply")?;
+        let asset_out = required(self.asset_out.clone(), "asset out")?;
+        let asset_in = required(self.asset_in.clone(), "asset in")?;
+        let asset_in_address = required(self.asset_in_address.clone(), "asset in address")?;
+        let asset_out_address = required(self.asset_out_address.clone(), "asset out address")?;
+        let mut pool = MpPool::new(context, total_supply)
+            .with_asset(&asset_in_address, asset_in)
+            .with_asset(&asset_out_address, asset_out);
+        let before = pool.clone();
         let shares;
-        let amount_out_min;
-        let amount_in_max;
-        match self.quantity.expect("sending or receiving amount not set") {
-            SidedQuantity::QuantityIn(amount_in) => {
-                let amount_out_mint = context
-                    .mint(&mut asset_in, amount_in)
-                    .expect("failed to calculate");
-
-                shares = amount_out_mint * asset_in.price * total_supply
-                    / context.total_current_usd_amount;
-
-                let amount_in_burn =
-                    shares * context.total_current_usd_amount / total_supply / asset_out.price;
-
-                let amount_out = context
-                    .burn(&mut asset_out, amount_in_burn)
-                    .expect("failed to calculate");
-
-                amount_out_min = match self.slippage {
-                    Some(s) => match s {
-                        Slippage::Percent(p) => amount_out + amount_out * p,
-                    },
-                    None => amount_out,
-                };
-                amount_in_max = amount_in;
+        let amount_in;
+        let amount_out;
+        match required(self.quantity, "sending or receiving amount")? {
+            SidedQuantity::QuantityIn(amount) => {
+                amount_in = amount;
+                shares = pool.mint(&asset_in_address, amount)?;
+                amount_out = pool.burn(&asset_out_address, shares)?;
             }
-            SidedQuantity::QuantityOut(amount_out) => {
-                let amount_in_burn = context
-                    .burn_rev(&mut asset_out, amount_out)
-                    .expect("failed to calculate");
-
-                shares = amount_in_burn * asset_out.price * total_supply
-                    / context.total_current_usd_amount;
+            SidedQuantity::QuantityOut(amount) => {
+                amount_out = amount;
+                shares = pool.burn_rev(&asset_out_address, amount)?;
+                amount_in = pool.mint_rev(&asset_in_address, shares)?;
+            }
+        }
+        let impact = PriceImpact::swap(
+            &before,
+            &asset_in_address,
+            &asset_out_address,
+            amount_in,
+            amount_out,
+        )?;
+        let quote = Quote {
+            amount_in,
+            amount_out,
+            shares,
+            amount_in_max: self.max_in(amount_in, amount_out)?,
+            amount_out_min: self.min_out(amount_in, amount_out)?,
+            ..self.base_quote(QuoteOperation::Swap, &before, &pool, impact, deadline)?
+        };
+        self.swap_params = quote.swap_params();
+        self.price_impact = Some(impact);
+        self.quote = Some(quote);
+        Ok(self)
+    }
 
-                let amount_out_mint =
-                    shares * context.total_current_usd_amount / total_supply / asset_in.price;
+    /// Recalculates `quote` against the fetched state at `now`, keeping its
+    /// deadline and bounds.
+    pub(super) fn requote<E>(mut self, quote: &Quote, now: Num) -> Result<Self, ActionError<E>> {
+        if quote.expired(now) {
+            return Err(ActionError::DeadlineExpired {
+                deadline: quote.deadline,
+                now,
+            });
+        }
+        let deadline = (quote.deadline, quote.deadline_kind);
+        self = match quote.operation {
+            QuoteOperation::Mint => self.compute_mint(deadline)?,
+            QuoteOperation::Burn => self.compute_burn(deadline)?,
+            QuoteOperation::Swap => self.compute_swap(deadline)?,
+        };
+        let mut fresh = required(self.quote.take(), "quote")?;
+        if fresh.amount_out < quote.amount_out_min {
+            return Err(ActionError::SlippageExceeded {
+                quoted: fresh.amount_out,
+                bound: quote.amount_out_min,
+            });
+        }
+        if fresh.amount_in > quote.amount_in_max {
+            return Err(ActionError::SlippageExceeded {
+                quoted: fresh.amount_in,
+                bound: quote.amount_in_max,
+            });
+        }
+        fresh.amount_out_min = quote.amount_out_min;
+        fresh.amount_in_max = quote.amount_in_max;
+        fresh.permit = quote.permit.clone();
+        self.mint_params = fresh.mint_params();
+        self.burn_params = fresh.burn_params();
+        self.swap_params = fresh.swap_params();
+        self.quote = Some(fresh);
+        Ok(self)
+    }
 
-                let amount_in = context
-                    .mint_rev(&mut asset_in, amount_out_mint)
-                    .expect("failed to calculate");
+    /// Quote of the trade taking the pool from `before` to `after`, with
+    /// amounts and bounds left zero.
+    fn base_quote<E>(
+        &self,
+        operation: QuoteOperation,
+        before: &MpPool,
+        after: &MpPool,
+        price_impact: PriceImpact,
+        (deadline, deadline_kind): (Num, DeadlineKind),
+    ) -> Result<Quote, ActionError<E>> {
+        let (fee, cashback) = after.fees_since(before);
+        Ok(Quote {
+            operation,
+            pool_address: required(self.pool_address.clone(), "pool address")?,
+            asset_in_address: match operation {
+                QuoteOperation::Burn => None,
+                _ => self.asset_in_address.clone(),
+            },
+            asset_out_address: match operation {
+                QuoteOperation::Mint => None,
+                _ => self.asset_out_address.clone(),
+            },
+            receiver_address: required(self.receiver_address.clone(), "receiver address")?,
+            amount_in: Num::ZERO,
+            amount_out: Num::ZERO,
+            shares: Num::ZERO,
+            amount_in_max: Num::ZERO,
+            amount_out_min: Num::ZERO,
+            fees: QuoteFees { fee, cashback },
+            price_impact,
+            before: before.into(),
+            after: after.into(),
+            block: required(self.block, "fetched block")?,
+            deadline,
+            deadline_kind,
+            permit: None,
+        })
+    }
 
-                amount_in_max = match self.slippage {
-                    Some(s) => match s {
-                        Slippage::Percent(p) => amount_in + amount_in * p,
-                    },
-                    None => amount_out,
-                };
-                amount_out_min = amount_out;
-            }
+    /// Amount out bound of a trade quoted at `amount_in` for `amount_out`.
+    /// Slippage loosens both sides whichever one was given, since the pool
+    /// settles trades in exact shares. Absolute bounds the quote already
+    /// breaks are rejected.
+    fn min_out<E>(&self, amount_in: Num, amount_out: Num) -> Result<Num, ActionError<E>> {
+        let Some(slippage) = self.slippage else {
+            return Ok(amount_out);
+        };
+        let bound = slippage.min_out(amount_in, amount_out);
+        if bound > amount_out {
+            return Err(ActionError::SlippageExceeded {
+                quoted: amount_out,
+                bound,
+            });
         }
-        let deadline = match self.deadline {
-            Some(dl) => match dl {
-                Deadline::Block(b) => b,
-                Deadline::BlockInterval(i) => adapter.get_current_block() + i,
-            },
-            None => Num::ZERO,
+        Ok(bound)
+    }
+
+    /// Amount in bound of a trade quoted at `amount_in` for `amount_out`.
+    fn max_in<E>(&self, amount_in: Num, amount_out: Num) -> Result<Num, ActionError<E>> {
+        let Some(slippage) = self.slippage else {
+            return Ok(amount_in);
         };
-        self.swap_params = Some(SwapTxnParams {
-            pool_address: self
-                .pool_address
-                .as_ref()
-                .expect("pool address not set")
-                .to_owned(),
-            asset_out_address: self
-                .asset_out_address
-                .as_ref()
-                .expect("asset out address not set")
-                .to_owned(),
-            asset_in_address: self
-                .asset_in_address
-                .as_ref()
-                .expect("asset in address not set")
-                .to_owned(),
-            shares,
-            amount_out_min,
-            amount_in_max,
-            receiver_address: self
-                .receiver_address
-                .as_ref()
-                .expect("receiver address not set")
-                .to_owned(),
-            deadline,
-        });
-        self
+        let bound = slippage.max_in(amount_in, amount_out);
+        if bound < amount_in {
+            return Err(ActionError::SlippageExceeded {
+                quoted: amount_in,
+                bound,
+            });
+        }
+        Ok(bound)
     }
 }
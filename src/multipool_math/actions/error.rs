@@ -0,0 +1,80 @@
+use std::fmt::{Debug, Display, Formatter};
+
+use crate::multipool_math::MpError;
+use crate::num::num::Num;
+
+/// Failure of an `ActionSettings` stage, generic over the adapter error.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ActionError<E> {
+    /// A setting or fetched value required by the stage is missing.
+    MissingField(&'static str),
+    Adapter(E),
+    Math(MpError),
+    /// The quoted amount already violates the slippage bound.
+    SlippageExceeded {
+        quoted: Num,
+        bound: Num,
+    },
+    /// The deadline of the quote has passed.
+    DeadlineExpired {
+        deadline: Num,
+        now: Num,
+    },
+    /// No path between the assets executes through the known pools,
+    /// `cause` being why the last path tried failed to execute.
+    NoRoute {
+        asset_in: String,
+        asset_out: String,
+        cause: Option<MpError>,
+    },
+}
+
+impl<E> From<MpError> for ActionError<E> {
+    fn from(error: MpError) -> Self {
+        ActionError::Math(error)
+    }
+}
+
+impl<E: Display> Display for ActionError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActionError::MissingField(field) => write!(f, "{field} not set"),
+            ActionError::Adapter(error) => write!(f, "adapter: {error}"),
+            ActionError::Math(error) => write!(f, "{error}"),
+            ActionError::SlippageExceeded { quoted, bound } => {
+                write!(f, "quoted amount {quoted} is beyond slippage bound {bound}")
+            }
+            ActionError::DeadlineExpired { deadline, now } => {
+                write!(f, "deadline {deadline} has passed at {now}")
+            }
+            ActionError::NoRoute {
+                asset_in,
+                asset_out,
+                cause,
+            } => {
+                write!(f, "no route from {asset_in} to {asset_out}")?;
+                match cause {
+                    Some(cause) => write!(f, ": {cause}"),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl<E: Display + Debug> std::error::Error for ActionError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ActionError::Math(error)
+            | ActionError::NoRoute {
+                cause: Some(error), ..
+            } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Unwraps a setting of an `ActionSettings` stage.
+pub(super) fn required<T, E>(value: Option<T>, field: &'static str) -> Result<T, ActionError<E>> {
+    value.ok_or(ActionError::MissingField(field))
+}
@@ -1,11 +1,21 @@
 pub mod adapter;
+pub mod approval;
+pub mod async_adapter;
+pub mod async_setup;
+pub mod builder;
 pub mod computation;
+pub mod error;
+pub mod quote;
+pub mod routing;
+pub mod schedule;
 pub mod settings;
 pub mod setup;
 #[cfg(test)]
 pub mod tests;
 
 use crate::num::num::Num;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Clone, Copy, Debug)]
 pub enum SidedQuantity {
@@ -13,17 +23,103 @@ pub enum SidedQuantity {
     QuantityOut(Num),
 }
 
-/// Value specifies slippage
-/// note: currently only percent specification is supported but there
-/// might be made amountIn/amountOut (price) parameter constrained
-/// by maximum and minimum
+/// Value specifies slippage. Relative tolerances loosen both quoted amounts
+/// of the trade, absolute bounds and the price limit replace them. Prices are
+/// quoted in units of amount in per unit of amount out, shares being the
+/// amount out of mints and the amount in of burns.
 #[derive(Clone, Copy, Debug)]
 pub enum Slippage {
+    /// Fraction of the quoted amount, `0.01` being one percent.
     Percent(Num),
+    /// Basis points of the quoted amount.
+    Bps(Num),
+    MinOut(Num),
+    MaxIn(Num),
+    PriceLimit(Num),
+}
+
+impl Slippage {
+    fn tolerance(&self) -> Option<Num> {
+        match self {
+            Slippage::Percent(p) => Some(*p),
+            Slippage::Bps(b) => Some(*b / Num::from("10000")),
+            _ => None,
+        }
+    }
+
+    /// Relative tolerance of each of `legs` chained trades, whose product
+    /// stays within this one. `None` for absolute bounds.
+    pub fn per_leg(&self, legs: usize) -> Option<Slippage> {
+        let legs = Num::with_decimals((legs.max(1) as u64).into(), 0);
+        Some(Slippage::Percent(self.tolerance()? / legs))
+    }
+
+    /// Smallest acceptable amount out for a quote of `amount_in` for
+    /// `amount_out`.
+    pub fn min_out(&self, amount_in: Num, amount_out: Num) -> Num {
+        if let Some(t) = self.tolerance() {
+            return amount_out.checked_sub(amount_out * t).unwrap_or(Num::ZERO);
+        }
+        match self {
+            Slippage::MinOut(min) => *min,
+            Slippage::PriceLimit(price) => amount_in.checked_div(*price).unwrap_or(Num::MAX),
+            _ => amount_out,
+        }
+    }
+
+    /// Largest acceptable amount in for a quote of `amount_in` for
+    /// `amount_out`.
+    pub fn max_in(&self, amount_in: Num, amount_out: Num) -> Num {
+        if let Some(t) = self.tolerance() {
+            return amount_in + amount_in * t;
+        }
+        match self {
+            Slippage::MaxIn(max) => *max,
+            Slippage::PriceLimit(price) => amount_out * *price,
+            _ => amount_in,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
 pub enum Deadline {
     BlockInterval(Num),
     Block(Num),
+    /// Unix timestamp in seconds.
+    Timestamp(Num),
+    /// Time to live counted from the current block timestamp.
+    Ttl(Duration),
+}
+
+/// What the deadline of transaction params is measured in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum DeadlineKind {
+    #[default]
+    None,
+    Block,
+    Timestamp,
+}
+
+impl Deadline {
+    /// Clock a relative deadline is counted from, `None` for absolute ones.
+    pub fn clock(&self) -> Option<DeadlineKind> {
+        match self {
+            Deadline::BlockInterval(_) => Some(DeadlineKind::Block),
+            Deadline::Ttl(_) => Some(DeadlineKind::Timestamp),
+            Deadline::Block(_) | Deadline::Timestamp(_) => None,
+        }
+    }
+
+    /// Absolute deadline, `now` being the current value of the `clock`.
+    pub fn resolve(&self, now: Num) -> (Num, DeadlineKind) {
+        match self {
+            Deadline::Block(b) => (*b, DeadlineKind::Block),
+            Deadline::BlockInterval(i) => (now + *i, DeadlineKind::Block),
+            Deadline::Timestamp(t) => (*t, DeadlineKind::Timestamp),
+            Deadline::Ttl(ttl) => {
+                let ttl = Num::with_decimals(ttl.as_secs().into(), 0);
+                (now + ttl, DeadlineKind::Timestamp)
+            }
+        }
+    }
 }
@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+
+use super::approval::Permit;
+use super::settings::{BurnTxnParams, MintTxnParams, SwapTxnParams};
+use super::DeadlineKind;
+use crate::multipool_math::{impact::PriceImpact, pool::MpPool, valuation::ValueUnit};
+use crate::num::num::Num;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum QuoteOperation {
+    Mint,
+    Burn,
+    Swap,
+}
+
+/// Usd value of fees collected from the trade and cashback paid to it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct QuoteFees {
+    pub fee: Num,
+    pub cashback: Num,
+}
+
+/// Pool totals on one side of the quoted trade.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct PoolSnapshot {
+    pub total_supply: Num,
+    pub total_usd: Num,
+    pub share_price: Num,
+}
+
+impl From<&MpPool> for PoolSnapshot {
+    fn from(pool: &MpPool) -> Self {
+        Self {
+            total_supply: pool.total_supply,
+            total_usd: pool.context.total_current_usd_amount,
+            share_price: pool.share_price(&ValueUnit::Usd).unwrap_or(Num::ZERO),
+        }
+    }
+}
+
+/// Calculated trade, ready to be confirmed with `send`. Shares are the
+/// amount out of mints and the amount in of burns, swaps route `shares`
+/// between their two assets.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Quote {
+    pub operation: QuoteOperation,
+    pub pool_address: String,
+    pub asset_in_address: Option<String>,
+    pub asset_out_address: Option<String>,
+    pub receiver_address: String,
+    pub amount_in: Num,
+    pub amount_out: Num,
+    pub shares: Num,
+    pub amount_in_max: Num,
+    pub amount_out_min: Num,
+    pub fees: QuoteFees,
+    pub price_impact: PriceImpact,
+    pub before: PoolSnapshot,
+    pub after: PoolSnapshot,
+    /// Block the pool state was fetched at.
+    pub block: Num,
+    pub deadline: Num,
+    pub deadline_kind: DeadlineKind,
+    /// Permit signed by `ensure_allowance`.
+    pub permit: Option<Permit>,
+}
+
+/// Result of sending a quote, one variant per operation.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TxnResult<M, B, S> {
+    Mint(M),
+    Burn(B),
+    Swap(S),
+}
+
+impl Quote {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Whether the deadline has passed at `now`, measured in the clock of
+    /// the deadline.
+    pub fn expired(&self, now: Num) -> bool {
+        self.deadline_kind != DeadlineKind::None && now > self.deadline
+    }
+
+    /// Transaction params of a mint quote.
+    pub fn mint_params(&self) -> Option<MintTxnParams> {
+        if self.operation != QuoteOperation::Mint {
+            return None;
+        }
+        Some(MintTxnParams {
+            pool_address: self.pool_address.clone(),
+            asset_in_address: self.asset_in_address.clone()?,
+            shares: self.shares,
+            amount_in_max: self.amount_in_max,
+            receiver_address: self.receiver_address.clone(),
+            deadline: self.deadline,
+            deadline_kind: self.deadline_kind,
+            permit: self.permit.clone(),
+        })
+    }
+
+    /// Transaction params of a burn quote.
+    pub fn burn_params(&self) -> Option<BurnTxnParams> {
+        if self.operation != QuoteOperation::Burn {
+            return None;
+        }
+        Some(BurnTxnParams {
+            pool_address: self.pool_address.clone(),
+            asset_out_address: self.asset_out_address.clone()?,
+            shares: self.shares,
+            amount_out_min: self.amount_out_min,
+            receiver_address: self.receiver_address.clone(),
+            deadline: self.deadline,
+            deadline_kind: self.deadline_kind,
+        })
+    }
+
+    /// Transaction params of a swap quote.
+    pub fn swap_params(&self) -> Option<SwapTxnParams> {
+        if self.operation != QuoteOperation::Swap {
+            return None;
+        }
+        Some(SwapTxnParams {
+            pool_address: self.pool_address.clone(),
+            asset_in_address: self.asset_in_address.clone()?,
+            asset_out_address: self.asset_out_address.clone()?,
+            shares: self.shares,
+            amount_in_max: self.amount_in_max,
+            amount_out_min: self.amount_out_min,
+            receiver_address: self.receiver_address.clone(),
+            deadline: self.deadline,
+            deadline_kind: self.deadline_kind,
+            permit: self.permit.clone(),
+        })
+    }
+}
@@ -0,0 +1,494 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use super::adapter::MpAdapter;
+use super::error::{required, ActionError};
+use super::quote::{Quote, QuoteFees, QuoteOperation};
+use super::schedule::split_amount;
+use super::{Deadline, DeadlineKind, Slippage};
+use crate::multipool_math::{impact::PriceImpact, pool::MpPool, MpError};
+use crate::num::num::Num;
+
+/// Trade through one or more pools. Every leg is a separate transaction
+/// whose quote can be sent with `ActionSettings::send`. Legs of a path are
+/// chained, legs of a split trade side by side.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Route {
+    pub legs: Vec<Quote>,
+    pub amount_in: Num,
+    pub amount_out: Num,
+}
+
+/// Single step of a path. Shares of a pool are traded as a token whose
+/// address is the pool address: mints pay them out and burns take them in.
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Hop {
+    operation: QuoteOperation,
+    pool_address: String,
+    asset_in: String,
+    asset_out: String,
+}
+
+/// Finds the best path from one asset to another across several pools.
+/// Paths are chains of swaps, mints and burns, so besides direct swaps the
+/// router finds swaps through an asset shared by two pools and trades
+/// through share tokens listed in another pool.
+pub struct Router<A> {
+    /// Assets listed in each pool, keyed by pool address.
+    pub pools: BTreeMap<String, Vec<String>>,
+    /// Account trading the route, which receives the output of every leg
+    /// of a path but the last.
+    pub sender_address: Option<String>,
+    pub receiver_address: Option<String>,
+    pub slippage: Option<Slippage>,
+    pub deadline: Option<Deadline>,
+    pub max_hops: usize,
+    pub adapter: A,
+}
+
+impl<A> Router<A> {
+    pub fn new(adapter: A) -> Self {
+        Self {
+            pools: BTreeMap::new(),
+            sender_address: None,
+            receiver_address: None,
+            slippage: None,
+            deadline: None,
+            max_hops: 3,
+            adapter,
+        }
+    }
+
+    pub fn pool<V, I>(mut self, address: V, assets: I) -> Self
+    where
+        V: Into<String>,
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.pools
+            .insert(address.into(), assets.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn sender<V: Into<String>>(mut self, val: V) -> Self {
+        self.sender_address = Some(val.into());
+        self
+    }
+
+    pub fn receiver<V: Into<String>>(mut self, val: V) -> Self {
+        self.receiver_address = Some(val.into());
+        self
+    }
+
+    /// Slippage of a route, spread evenly over the legs of a path and
+    /// applied in full to every leg of a split.
+    pub fn slippage_percent<V: Into<Num>>(mut self, val: V) -> Self {
+        self.slippage = Some(Slippage::Percent(val.into()));
+        self
+    }
+
+    pub fn slippage_bps<V: Into<Num>>(mut self, val: V) -> Self {
+        self.slippage = Some(Slippage::Bps(val.into()));
+        self
+    }
+
+    pub fn until_block<V: Into<Num>>(mut self, val: V) -> Self {
+        self.deadline = Some(Deadline::Block(val.into()));
+        self
+    }
+
+    pub fn blocks_to_live<V: Into<Num>>(mut self, val: V) -> Self {
+        self.deadline = Some(Deadline::BlockInterval(val.into()));
+        self
+    }
+
+    pub fn until_timestamp<V: Into<Num>>(mut self, val: V) -> Self {
+        self.deadline = Some(Deadline::Timestamp(val.into()));
+        self
+    }
+
+    pub fn time_to_live(mut self, val: Duration) -> Self {
+        self.deadline = Some(Deadline::Ttl(val));
+        self
+    }
+
+    pub fn max_hops(mut self, hops: usize) -> Self {
+        self.max_hops = hops;
+        self
+    }
+
+    /// Every trade a single transaction can make with `token`.
+    fn hops_from(&self, token: &str) -> Vec<Hop> {
+        let mut hops = Vec::new();
+        for (pool_address, assets) in &self.pools {
+            let hop = |operation, asset_in: &str, asset_out: &str| Hop {
+                operation,
+                pool_address: pool_address.clone(),
+                asset_in: asset_in.to_owned(),
+                asset_out: asset_out.to_owned(),
+            };
+            if pool_address == token {
+                hops.extend(
+                    assets
+                        .iter()
+                        .map(|asset| hop(QuoteOperation::Burn, token, asset)),
+                );
+            } else if assets.iter().any(|asset| asset == token) {
+                hops.extend(
+                    assets
+                        .iter()
+                        .filter(|asset| *asset != token)
+                        .map(|asset| hop(QuoteOperation::Swap, token, asset)),
+                );
+                hops.push(hop(QuoteOperation::Mint, token, pool_address));
+            }
+        }
+        hops
+    }
+
+    /// Paths of at most `max_hops` from `asset_in` to `asset_out` that
+    /// never return to a token.
+    fn paths(&self, asset_in: &str, asset_out: &str) -> Vec<Vec<Hop>> {
+        let mut paths = Vec::new();
+        let mut stack = vec![(asset_in.to_owned(), Vec::<Hop>::new())];
+        while let Some((token, path)) = stack.pop() {
+            if path.len() == self.max_hops {
+                continue;
+            }
+            for hop in self.hops_from(&token) {
+                let visited = hop.asset_out == asset_in
+                    || path.iter().any(|step| step.asset_out == hop.asset_out);
+                if visited {
+                    continue;
+                }
+                let mut next = path.clone();
+                next.push(hop.clone());
+                if hop.asset_out == asset_out {
+                    paths.push(next);
+                } else {
+                    stack.push((hop.asset_out, next));
+                }
+            }
+        }
+        paths
+    }
+
+    /// Leg quotes of `path` executed with `amount_in`, each leg trading
+    /// against the state left by the previous ones.
+    fn simulate<E>(
+        &self,
+        pools: &BTreeMap<String, MpPool>,
+        path: &[Hop],
+        amount_in: Num,
+        block: Num,
+    ) -> Result<Route, ActionError<E>> {
+        let mut pools = pools.clone();
+        let mut legs = Vec::with_capacity(path.len());
+        let mut amount = amount_in;
+        for hop in path {
+            let Some(pool) = pools.get_mut(&hop.pool_address) else {
+                return Err(ActionError::NoRoute {
+                    asset_in: hop.asset_in.clone(),
+                    asset_out: hop.asset_out.clone(),
+                    cause: None,
+                });
+            };
+            let leg = simulate_hop(pool, hop, amount)?;
+            amount = leg.amount_out;
+            legs.push(Quote { block, ..leg });
+        }
+        Ok(Route {
+            legs,
+            amount_in,
+            amount_out: amount,
+        })
+    }
+
+    /// Bounds the legs of the path `route` by an even share of the
+    /// slippage, so that together they stay within it. Absolute bounds
+    /// have no share and limit the first and last leg instead. A leg never
+    /// spends more than the previous leg is bound to pay out.
+    fn bound(&self, route: &mut Route) {
+        let Some(slippage) = self.slippage else {
+            return;
+        };
+        if let Some(per_leg) = slippage.per_leg(route.legs.len()) {
+            route
+                .legs
+                .iter_mut()
+                .for_each(|leg| bound_leg(leg, per_leg));
+        } else {
+            if let Some(first) = route.legs.first_mut() {
+                first.amount_in_max = slippage.max_in(route.amount_in, route.amount_out);
+            }
+            if let Some(last) = route.legs.last_mut() {
+                last.amount_out_min = slippage.min_out(route.amount_in, route.amount_out);
+            }
+        }
+        for index in 1..route.legs.len() {
+            let paid_out = route.legs[index - 1].amount_out_min;
+            let leg = &mut route.legs[index];
+            leg.amount_in_max = leg.amount_in_max.min(paid_out);
+        }
+    }
+}
+
+impl<A: MpAdapter> Router<A> {
+    /// Block or timestamp the legs expire at, zero if no deadline is set.
+    fn deadline(&mut self) -> Result<(Num, DeadlineKind), ActionError<A::Error>> {
+        let Some(deadline) = self.deadline else {
+            return Ok((Num::ZERO, DeadlineKind::None));
+        };
+        let now = match deadline.clock() {
+            Some(DeadlineKind::Block) => self
+                .adapter
+                .get_current_block()
+                .map_err(ActionError::Adapter)?,
+            Some(DeadlineKind::Timestamp) => self
+                .adapter
+                .get_current_timestamp()
+                .map_err(ActionError::Adapter)?,
+            _ => Num::ZERO,
+        };
+        Ok(deadline.resolve(now))
+    }
+
+    /// Fetches the context, supply and listed assets of every pool.
+    fn fetch(&mut self) -> Result<BTreeMap<String, MpPool>, ActionError<A::Error>> {
+        let mut pools = BTreeMap::new();
+        for (address, assets) in &self.pools {
+            let adapter = &mut self.adapter;
+            let mut pool = MpPool::new(
+                adapter.get_context(address).map_err(ActionError::Adapter)?,
+                adapter
+                    .get_total_supply(address)
+                    .map_err(ActionError::Adapter)?,
+            );
+            for asset in assets {
+                pool = pool.with_asset(
+                    asset,
+                    adapter
+                        .get_asset(address, asset)
+                        .map_err(ActionError::Adapter)?,
+                );
+            }
+            pools.insert(address.clone(), pool);
+        }
+        Ok(pools)
+    }
+
+    /// Every path from `asset_in` to `asset_out` that can execute with
+    /// `amount_in`, the largest amount out first. Fails with the error of
+    /// the last path tried if paths exist but none of them executes.
+    pub fn routes<V: Into<Num>>(
+        &mut self,
+        asset_in: &str,
+        asset_out: &str,
+        amount_in: V,
+    ) -> Result<Vec<Route>, ActionError<A::Error>> {
+        let amount_in = amount_in.into();
+        let receiver_address = required(self.receiver_address.clone(), "receiver address")?;
+        let pools = self.fetch()?;
+        let block = self
+            .adapter
+            .get_current_block()
+            .map_err(ActionError::Adapter)?;
+        let deadline = self.deadline()?;
+        let mut routes = Vec::new();
+        let mut failure = None;
+        for path in self.paths(asset_in, asset_out) {
+            match self.simulate(&pools, &path, amount_in, block) {
+                Ok(route) => routes.push(route),
+                Err(error) => failure = Some(error),
+            }
+        }
+        if let (true, Some(error)) = (routes.is_empty(), failure) {
+            return Err(match error {
+                ActionError::Math(cause) => ActionError::NoRoute {
+                    asset_in: asset_in.to_owned(),
+                    asset_out: asset_out.to_owned(),
+                    cause: Some(cause),
+                },
+                error => error,
+            });
+        }
+        routes.sort_by(|a, b| {
+            b.amount_out
+                .cmp(&a.amount_out)
+                .then(a.legs.len().cmp(&b.legs.len()))
+        });
+        for route in &mut routes {
+            let last = route.legs.len() - 1;
+            for (index, leg) in route.legs.iter_mut().enumerate() {
+                leg.receiver_address = if index == last {
+                    receiver_address.clone()
+                } else {
+                    required(self.sender_address.clone(), "sender address")?
+                };
+                (leg.deadline, leg.deadline_kind) = deadline;
+            }
+            self.bound(route);
+        }
+        Ok(routes)
+    }
+
+    /// Swap of `amount_in` divided between every pool listing both assets.
+    /// The amount is allocated in `parts` equal increments, each going to
+    /// the pool paying the most for it given the increments it already got.
+    /// Returns one leg per pool that got a share of the amount, slippage
+    /// bounding each leg.
+    pub fn split_route<V: Into<Num>>(
+        &mut self,
+        asset_in: &str,
+        asset_out: &str,
+        amount_in: V,
+        parts: u32,
+    ) -> Result<Route, ActionError<A::Error>> {
+        let amount_in = amount_in.into();
+        let receiver_address = required(self.receiver_address.clone(), "receiver address")?;
+        let no_route = || ActionError::NoRoute {
+            asset_in: asset_in.to_owned(),
+            asset_out: asset_out.to_owned(),
+            cause: None,
+        };
+        let hops: Vec<Hop> = self
+            .pools
+            .iter()
+            .filter(|(_, assets)| {
+                assets.iter().any(|asset| asset == asset_in)
+                    && assets.iter().any(|asset| asset == asset_out)
+            })
+            .map(|(address, _)| Hop {
+                operation: QuoteOperation::Swap,
+                pool_address: address.clone(),
+                asset_in: asset_in.to_owned(),
+                asset_out: asset_out.to_owned(),
+            })
+            .collect();
+        if hops.is_empty() {
+            return Err(no_route());
+        }
+        let pools = self.fetch()?;
+        let block = self
+            .adapter
+            .get_current_block()
+            .map_err(ActionError::Adapter)?;
+        let (deadline, deadline_kind) = self.deadline()?;
+        let mut states: Vec<MpPool> = hops
+            .iter()
+            .map(|hop| pools[&hop.pool_address].clone())
+            .collect();
+        let mut allocations = vec![Num::ZERO; hops.len()];
+        for part in split_amount(amount_in, parts) {
+            let (index, _, state) = states
+                .iter()
+                .enumerate()
+                .filter_map(|(index, state)| {
+                    let mut state = state.clone();
+                    let (_, amount_out) = state.swap(asset_in, asset_out, part).ok()?;
+                    Some((index, amount_out, state))
+                })
+                .max_by(|a, b| a.1.cmp(&b.1))
+                .ok_or_else(no_route)?;
+            states[index] = state;
+            allocations[index] += part;
+        }
+        let legs = hops
+            .iter()
+            .zip(allocations)
+            .filter(|(_, allocation)| !allocation.is_zero())
+            .map(|(hop, allocation)| {
+                let mut pool = pools[&hop.pool_address].clone();
+                let mut leg = Quote {
+                    receiver_address: receiver_address.clone(),
+                    block,
+                    deadline,
+                    deadline_kind,
+                    ..simulate_hop(&mut pool, hop, allocation)?
+                };
+                if let Some(slippage) = self.slippage {
+                    bound_leg(&mut leg, slippage);
+                }
+                Ok(leg)
+            })
+            .collect::<Result<Vec<Quote>, MpError>>()?;
+        Ok(Route {
+            amount_in,
+            amount_out: legs.iter().map(|leg| leg.amount_out).sum(),
+            legs,
+        })
+    }
+
+    /// Route from `asset_in` to `asset_out` with the largest amount out.
+    pub fn best_route<V: Into<Num>>(
+        &mut self,
+        asset_in: &str,
+        asset_out: &str,
+        amount_in: V,
+    ) -> Result<Route, ActionError<A::Error>> {
+        self.routes(asset_in, asset_out, amount_in)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| ActionError::NoRoute {
+                asset_in: asset_in.to_owned(),
+                asset_out: asset_out.to_owned(),
+                cause: None,
+            })
+    }
+}
+
+/// Loosens both bounds of `leg` by `slippage`.
+fn bound_leg(leg: &mut Quote, slippage: Slippage) {
+    leg.amount_out_min = slippage.min_out(leg.amount_in, leg.amount_out);
+    leg.amount_in_max = slippage.max_in(leg.amount_in, leg.amount_out);
+}
+
+/// Applies `hop` to `pool` and quotes it. Bounds are the quoted amounts,
+/// receiver, block and deadline are left to the router.
+fn simulate_hop(pool: &mut MpPool, hop: &Hop, amount_in: Num) -> Result<Quote, MpError> {
+    let before = pool.clone();
+    let (shares, amount_out, price_impact) = match hop.operation {
+        QuoteOperation::Swap => {
+            let (shares, amount_out) = pool.swap(&hop.asset_in, &hop.asset_out, amount_in)?;
+            let impact = PriceImpact::swap(
+                &before,
+                &hop.asset_in,
+                &hop.asset_out,
+                amount_in,
+                amount_out,
+            )?;
+            (shares, amount_out, impact)
+        }
+        QuoteOperation::Mint => {
+            let shares = pool.mint(&hop.asset_in, amount_in)?;
+            let impact = PriceImpact::mint(&before, &hop.asset_in, amount_in, shares)?;
+            (shares, shares, impact)
+        }
+        QuoteOperation::Burn => {
+            let amount_out = pool.burn(&hop.asset_out, amount_in)?;
+            let impact = PriceImpact::burn(&before, &hop.asset_out, amount_in, amount_out)?;
+            (amount_in, amount_out, impact)
+        }
+    };
+    let (fee, cashback) = pool.fees_since(&before);
+    Ok(Quote {
+        operation: hop.operation,
+        pool_address: hop.pool_address.clone(),
+        asset_in_address: (hop.operation != QuoteOperation::Burn).then(|| hop.asset_in.clone()),
+        asset_out_address: (hop.operation != QuoteOperation::Mint).then(|| hop.asset_out.clone()),
+        receiver_address: String::new(),
+        amount_in,
+        amount_out,
+        shares,
+        amount_in_max: amount_in,
+        amount_out_min: amount_out,
+        fees: QuoteFees { fee, cashback },
+        price_impact,
+        before: (&before).into(),
+        after: (&*pool).into(),
+        block: Num::ZERO,
+        deadline: Num::ZERO,
+        deadline_kind: DeadlineKind::None,
+        permit: None,
+    })
+}
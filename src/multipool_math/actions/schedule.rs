@@ -0,0 +1,155 @@
+use super::adapter::MpAdapter;
+use super::error::{required, ActionError};
+use super::{settings::ActionSettings, SidedQuantity};
+use crate::multipool_math::pool::MpPool;
+use crate::num::num::Num;
+
+/// Expected outcome of executing a trade in `chunks` equal parts.
+#[derive(Clone, Debug)]
+pub struct ExecutionSchedule {
+    pub chunks: u32,
+    pub quantities: Vec<SidedQuantity>,
+    pub amounts_in: Vec<Num>,
+    pub amounts_out: Vec<Num>,
+    pub total_in: Num,
+    pub total_out: Num,
+    /// Usd value of base and deviation fees paid over all chunks.
+    pub total_fee: Num,
+    pub total_cashback: Num,
+}
+
+/// `total` divided into `chunks` equal parts.
+pub(super) fn split_amount(total: Num, chunks: u32) -> Vec<Num> {
+    let chunks = chunks.max(1);
+    let part = total / Num::with_decimals(chunks.into(), 0);
+    (0..chunks)
+        .map(|i| {
+            // the last chunk takes the rounding dust
+            if i == chunks - 1 {
+                total - part * Num::with_decimals((chunks - 1).into(), 0)
+            } else {
+                part
+            }
+        })
+        .collect()
+}
+
+fn split_quantity(quantity: SidedQuantity, chunks: u32) -> Vec<SidedQuantity> {
+    match quantity {
+        SidedQuantity::QuantityIn(v) => split_amount(v, chunks)
+            .into_iter()
+            .map(SidedQuantity::QuantityIn)
+            .collect(),
+        SidedQuantity::QuantityOut(v) => split_amount(v, chunks)
+            .into_iter()
+            .map(SidedQuantity::QuantityOut)
+            .collect(),
+    }
+}
+
+impl<A: MpAdapter + Clone> ActionSettings<A> {
+    /// Splits the trade into `chunks` settings with equal quantities. Each
+    /// chunk still has to be calculated and sent.
+    pub fn split(&self, chunks: u32) -> Result<Vec<ActionSettings<A>>, ActionError<A::Error>> {
+        let quantity = required(self.quantity, "sending or receiving amount")?;
+        Ok(split_quantity(quantity, chunks)
+            .into_iter()
+            .map(|quantity| {
+                let mut chunk = self.clone();
+                chunk.quantity = Some(quantity);
+                chunk.mint_params = None;
+                chunk.burn_params = None;
+                chunk.swap_params = None;
+                chunk.price_impact = None;
+                chunk.quote = None;
+                chunk
+            })
+            .collect())
+    }
+}
+
+impl<A: MpAdapter> ActionSettings<A> {
+    /// Simulates the trade split into each of `chunk_counts` parts. Between
+    /// chunks arbitrageurs revert `reversion` of the pool displacement,
+    /// where zero means no rebalancing and one means a full recovery.
+    pub fn schedules(
+        &self,
+        chunk_counts: &[u32],
+        reversion: Num,
+    ) -> Result<Vec<ExecutionSchedule>, ActionError<A::Error>> {
+        let quantity = required(self.quantity, "sending or receiving amount")?;
+        let mut pool = MpPool::new(
+            required(self.context.clone(), "context")?,
+            required(self.total_supply, "total supply")?,
+        );
+        if let Some(address) = &self.asset_in_address {
+            pool = pool.with_asset(address, required(self.asset_in.clone(), "asset in")?);
+        }
+        if let Some(address) = &self.asset_out_address {
+            pool = pool.with_asset(address, required(self.asset_out.clone(), "asset out")?);
+        }
+        chunk_counts
+            .iter()
+            .map(|chunks| {
+                let mut state = pool.clone();
+                let mut schedule = ExecutionSchedule {
+                    chunks: *chunks,
+                    quantities: split_quantity(quantity, *chunks),
+                    amounts_in: Vec::new(),
+                    amounts_out: Vec::new(),
+                    total_in: Num::ZERO,
+                    total_out: Num::ZERO,
+                    total_fee: Num::ZERO,
+                    total_cashback: Num::ZERO,
+                };
+                for quantity in &schedule.quantities {
+                    let before = state.clone();
+                    let (amount_in, amount_out) = self.execute(&mut state, *quantity)?;
+                    let (fee, cashback) = state.fees_since(&before);
+                    schedule.amounts_in.push(amount_in);
+                    schedule.amounts_out.push(amount_out);
+                    schedule.total_in += amount_in;
+                    schedule.total_out += amount_out;
+                    schedule.total_fee += fee;
+                    schedule.total_cashback += cashback;
+                    state.revert_towards(&pool, reversion);
+                }
+                Ok(schedule)
+            })
+            .collect()
+    }
+
+    /// Applies one chunk to the pool and returns amounts in and out. The
+    /// trade is a swap if both assets are set, otherwise a mint or a burn.
+    fn execute(
+        &self,
+        pool: &mut MpPool,
+        quantity: SidedQuantity,
+    ) -> Result<(Num, Num), ActionError<A::Error>> {
+        let asset_in = self.asset_in_address.as_deref();
+        let asset_out = self.asset_out_address.as_deref();
+        match (asset_in, asset_out, quantity) {
+            (Some(asset_in), Some(asset_out), SidedQuantity::QuantityIn(amount_in)) => {
+                let (_, amount_out) = pool.swap(asset_in, asset_out, amount_in)?;
+                Ok((amount_in, amount_out))
+            }
+            (Some(asset_in), Some(asset_out), SidedQuantity::QuantityOut(amount_out)) => {
+                let shares = pool.burn_rev(asset_out, amount_out)?;
+                Ok((pool.mint_rev(asset_in, shares)?, amount_out))
+            }
+            (Some(asset_in), None, SidedQuantity::QuantityIn(amount_in)) => {
+                Ok((amount_in, pool.mint(asset_in, amount_in)?))
+            }
+            (Some(asset_in), None, SidedQuantity::QuantityOut(shares)) => {
+                Ok((pool.mint_rev(asset_in, shares)?, shares))
+            }
+            (None, Some(asset_out), SidedQuantity::QuantityIn(shares)) => {
+                Ok((shares, pool.burn(asset_out, shares)?))
+            }
+            (None, Some(asset_out), SidedQuantity::QuantityOut(amount_out)) => {
+                Ok((pool.burn_rev(asset_out, amount_out)?, amount_out))
+            }
+            (None, None, _) => Err(ActionError::MissingField("asset in address")),
+        }
+    }
+}
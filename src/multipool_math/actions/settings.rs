@@ -1,26 +1,34 @@
 use crate::num::num::Num;
 
-use super::adapter::MpAdapter;
-use super::{Deadline, SidedQuantity, Slippage};
-use crate::multipool_math::{MpAsset, MpContext};
+use super::{
+    approval::{Approval, Permit},
+    quote::Quote,
+    Deadline, DeadlineKind, SidedQuantity, Slippage,
+};
+use crate::multipool_math::{impact::PriceImpact, MpAsset, MpContext};
 
-#[derive(Default, Debug)]
-pub struct ActionSettings<A: MpAdapter> {
+#[derive(Default, Debug, Clone)]
+pub struct ActionSettings<A> {
     pub quantity: Option<SidedQuantity>,
     pub slippage: Option<Slippage>,
     pub asset_in: Option<MpAsset>,
     pub asset_out: Option<MpAsset>,
     pub context: Option<MpContext>,
     pub total_supply: Option<Num>,
+    /// Block the pool state was fetched at.
+    pub block: Option<Num>,
     pub pool_address: Option<String>,
     pub asset_in_address: Option<String>,
     pub asset_out_address: Option<String>,
     pub receiver_address: Option<String>,
     pub deadline: Option<Deadline>,
+    pub approval: Approval,
     // calculations outcome to send to router
     pub mint_params: Option<MintTxnParams>,
     pub burn_params: Option<BurnTxnParams>,
     pub swap_params: Option<SwapTxnParams>,
+    pub price_impact: Option<PriceImpact>,
+    pub quote: Option<Quote>,
     // adapter that lets you operate data fetching and other things
     pub adapter: Option<A>,
 }
@@ -33,6 +41,8 @@ pub struct MintTxnParams {
     pub amount_in_max: Num,
     pub receiver_address: String,
     pub deadline: Num,
+    pub deadline_kind: DeadlineKind,
+    pub permit: Option<Permit>,
 }
 
 #[derive(Clone, Debug)]
@@ -43,6 +53,7 @@ pub struct BurnTxnParams {
     pub amount_out_min: Num,
     pub receiver_address: String,
     pub deadline: Num,
+    pub deadline_kind: DeadlineKind,
 }
 
 #[derive(Clone, Debug)]
@@ -55,4 +66,6 @@ pub struct SwapTxnParams {
     pub amount_out_min: Num,
     pub receiver_address: String,
     pub deadline: Num,
+    pub deadline_kind: DeadlineKind,
+    pub permit: Option<Permit>,
 }
@@ -1,9 +1,13 @@
 use crate::num::num::Num;
+use std::time::Duration;
 
-use super::adapter::MpAdapter;
+use super::adapter::{MpAdapter, MpTxnResult};
+use super::approval::Approval;
+use super::error::{required, ActionError};
+use super::quote::{Quote, QuoteOperation, TxnResult};
 use super::{settings::ActionSettings, Deadline, SidedQuantity, Slippage};
 
-impl<A: MpAdapter> ActionSettings<A> {
+impl<A> ActionSettings<A> {
     pub fn new(adapter: A) -> Self {
         Self {
             quantity: None,
@@ -12,14 +16,18 @@ impl<A: MpAdapter> ActionSettings<A> {
             asset_out: None,
             context: None,
             total_supply: None,
+            block: None,
             pool_address: None,
             asset_in_address: None,
             asset_out_address: None,
             receiver_address: None,
             deadline: None,
+            approval: Approval::Transaction,
             mint_params: None,
             burn_params: None,
             swap_params: None,
+            price_impact: None,
+            quote: None,
             adapter: Some(adapter),
         }
     }
@@ -39,6 +47,26 @@ impl<A: MpAdapter> ActionSettings<A> {
         self
     }
 
+    pub fn slippage_bps<V: Into<Num>>(mut self, val: V) -> Self {
+        self.slippage = Some(Slippage::Bps(val.into()));
+        self
+    }
+
+    pub fn min_amount_out<V: Into<Num>>(mut self, val: V) -> Self {
+        self.slippage = Some(Slippage::MinOut(val.into()));
+        self
+    }
+
+    pub fn max_amount_in<V: Into<Num>>(mut self, val: V) -> Self {
+        self.slippage = Some(Slippage::MaxIn(val.into()));
+        self
+    }
+
+    pub fn price_limit<V: Into<Num>>(mut self, val: V) -> Self {
+        self.slippage = Some(Slippage::PriceLimit(val.into()));
+        self
+    }
+
     pub fn pool<V: Into<String>>(mut self, val: V) -> Self {
         self.pool_address = Some(val.into());
         self
@@ -69,59 +97,89 @@ impl<A: MpAdapter> ActionSettings<A> {
         self
     }
 
-    pub fn fetch(mut self) -> Self {
-        let adapter = self.adapter.as_mut().expect("adapter not set");
-        self.context = adapter
-            .get_context(self.pool_address.as_ref().expect("pool address not set"))
-            .ok();
-        self.total_supply = adapter
-            .get_total_supply(&self.pool_address.as_ref().expect("pool address not set"))
-            .ok();
-        self.asset_in = adapter
-            .get_asset(
-                &self.pool_address.as_ref().expect("pool address not set"),
-                &self
-                    .asset_in_address
-                    .as_ref()
-                    .expect("asset in address not set"),
-            )
-            .ok();
-        self.asset_out = adapter
-            .get_asset(
-                &self.pool_address.as_ref().expect("pool address not set"),
-                &self
-                    .asset_in_address
-                    .as_ref()
-                    .expect("asset out address not set"),
-            )
-            .ok();
+    pub fn until_timestamp<V: Into<Num>>(mut self, val: V) -> Self {
+        self.deadline = Some(Deadline::Timestamp(val.into()));
         self
     }
 
-    pub fn send_mint(&mut self) -> A::MintTxnResult {
-        let adapter = self.adapter.as_mut().expect("adapter not set");
-        adapter.transact_mint(
-            self.mint_params
-                .as_ref()
-                .expect("params are not calculated"),
-        )
+    pub fn time_to_live(mut self, val: Duration) -> Self {
+        self.deadline = Some(Deadline::Ttl(val));
+        self
+    }
+}
+
+impl<A: MpAdapter> ActionSettings<A> {
+    /// Fetches the pool context, share supply, current block and every
+    /// asset whose address is set.
+    pub fn fetch(mut self) -> Result<Self, ActionError<A::Error>> {
+        let adapter = required(self.adapter.as_mut(), "adapter")?;
+        let pool_address = required(self.pool_address.as_ref(), "pool address")?;
+        if self.asset_in_address.is_none() && self.asset_out_address.is_none() {
+            return Err(ActionError::MissingField("asset address"));
+        }
+        self.context = Some(
+            adapter
+                .get_context(pool_address)
+                .map_err(ActionError::Adapter)?,
+        );
+        self.total_supply = Some(
+            adapter
+                .get_total_supply(pool_address)
+                .map_err(ActionError::Adapter)?,
+        );
+        self.block = Some(adapter.get_current_block().map_err(ActionError::Adapter)?);
+        if let Some(address) = &self.asset_in_address {
+            self.asset_in = Some(
+                adapter
+                    .get_asset(pool_address, address)
+                    .map_err(ActionError::Adapter)?,
+            );
+        }
+        if let Some(address) = &self.asset_out_address {
+            self.asset_out = Some(
+                adapter
+                    .get_asset(pool_address, address)
+                    .map_err(ActionError::Adapter)?,
+            );
+        }
+        Ok(self)
+    }
+
+    pub fn send_mint(&mut self) -> Result<A::MintTxnResult, ActionError<A::Error>> {
+        let adapter = required(self.adapter.as_mut(), "adapter")?;
+        let params = required(self.mint_params.as_ref(), "mint params")?;
+        Ok(adapter.transact_mint(params))
+    }
+
+    pub fn send_burn(&mut self) -> Result<A::BurnTxnResult, ActionError<A::Error>> {
+        let adapter = required(self.adapter.as_mut(), "adapter")?;
+        let params = required(self.burn_params.as_ref(), "burn params")?;
+        Ok(adapter.transact_burn(params))
     }
 
-    pub fn send_burn(&mut self) -> A::BurnTxnResult {
-        let adapter = self.adapter.as_mut().expect("adapter not set");
-        adapter.transact_burn(
-            self.burn_params
-                .as_ref()
-                .expect("params are not calculated"),
-        )
+    pub fn send_swap(&mut self) -> Result<A::SwapTxnResult, ActionError<A::Error>> {
+        let adapter = required(self.adapter.as_mut(), "adapter")?;
+        let params = required(self.swap_params.as_ref(), "swap params")?;
+        Ok(adapter.transact_swap(params))
     }
 
-    pub fn send_swap(&mut self) -> A::SwapTxnResult {
-        let adapter = self.adapter.as_mut().expect("adapter not set");
-        adapter.transact_swap(
-            self.swap_params
-                .as_ref()
-                .expect("params are not calculated"),
-        )
+    /// Sends the transaction of a quote, possibly calculated by another
+    /// `ActionSettings`.
+    pub fn send(&mut self, quote: &Quote) -> Result<MpTxnResult<A>, ActionError<A::Error>> {
+        let adapter = required(self.adapter.as_mut(), "adapter")?;
+        Ok(match quote.operation {
+            QuoteOperation::Mint => {
+                let params = required(quote.mint_params(), "mint params")?;
+                TxnResult::Mint(adapter.transact_mint(&params))
+            }
+            QuoteOperation::Burn => {
+                let params = required(quote.burn_params(), "burn params")?;
+                TxnResult::Burn(adapter.transact_burn(&params))
+            }
+            QuoteOperation::Swap => {
+                let params = required(quote.swap_params(), "swap params")?;
+                TxnResult::Swap(adapter.transact_swap(&params))
+            }
+        })
     }
 }
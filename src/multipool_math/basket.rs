@@ -0,0 +1,175 @@
+use super::{pool::MpPool, MpAsset, MpError, MpOperation};
+use crate::num::{num::Num, snum::SNum};
+
+/// Result of a single leg of a basket operation. Fees and cashback are in
+/// units of the leg asset.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BasketLeg {
+    pub address: String,
+    /// Supplied quantity on mint, paid out quantity on burn.
+    pub quantity: Num,
+    pub shares: Num,
+    pub base_fee: Num,
+    pub deviation_fee: Num,
+    pub cashback: Num,
+}
+
+/// Legs in the order they were applied and the total of shares issued or
+/// burned.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BasketOutcome {
+    pub shares: Num,
+    pub legs: Vec<BasketLeg>,
+}
+
+impl BasketLeg {
+    fn new(address: &str, before: &MpAsset, after: &MpAsset, quantity: Num, shares: Num) -> Self {
+        let (deviation_fee, cashback) = if after.collected_cashbacks >= before.collected_cashbacks {
+            (
+                after.collected_cashbacks - before.collected_cashbacks,
+                Num::ZERO,
+            )
+        } else {
+            (
+                Num::ZERO,
+                before.collected_cashbacks - after.collected_cashbacks,
+            )
+        };
+        Self {
+            address: address.to_owned(),
+            quantity,
+            shares,
+            base_fee: after.collected_fees - before.collected_fees,
+            deviation_fee,
+            cashback,
+        }
+    }
+
+    /// Usd cost of the leg, negative when cashback outweighs the fees.
+    pub fn cost(&self, price: Num) -> SNum {
+        SNum::from((self.base_fee + self.deviation_fee) * price) - SNum::from(self.cashback * price)
+    }
+}
+
+impl MpPool {
+    /// Mints with several assets at once. Legs are applied greedily, each
+    /// time picking the leg that is currently the cheapest, so legs moving
+    /// assets towards their targets go first and earn cashback.
+    pub fn mint_basket(&mut self, legs: &[(&str, Num)]) -> Result<BasketOutcome, MpError> {
+        let legs = legs
+            .iter()
+            .map(|(address, quantity)| (address.to_string(), *quantity))
+            .collect();
+        self.apply_basket(legs, MpOperation::Mint)
+    }
+
+    /// Burns `shares` across all pool assets proportionally to their usd
+    /// value.
+    pub fn burn_proportional(&mut self, shares: Num) -> Result<BasketOutcome, MpError> {
+        let weights: Vec<(String, Num)> = self
+            .assets
+            .iter()
+            .map(|(address, asset)| (address.clone(), asset.quantity * asset.price))
+            .collect();
+        let weights: Vec<(&str, Num)> = weights
+            .iter()
+            .map(|(address, weight)| (address.as_str(), *weight))
+            .collect();
+        self.burn_basket(shares, &weights)
+    }
+
+    /// Burns `shares` split between assets by explicit weights. Weights
+    /// don't have to sum up to one.
+    pub fn burn_basket(
+        &mut self,
+        shares: Num,
+        weights: &[(&str, Num)],
+    ) -> Result<BasketOutcome, MpError> {
+        let operation = MpOperation::Burn;
+        let overflow = || MpError::Overflow { operation };
+        let total_weight = weights
+            .iter()
+            .try_fold(Num::ZERO, |acc, (_, weight)| acc.checked_add(*weight))
+            .ok_or_else(overflow)?;
+        if total_weight.is_zero() {
+            return Err(MpError::EmptyBasket { operation });
+        }
+        let last = weights.iter().rposition(|(_, weight)| !weight.is_zero());
+        let mut legs = Vec::new();
+        let mut remaining = shares;
+        for (i, (address, weight)) in weights.iter().enumerate() {
+            if weight.is_zero() {
+                continue;
+            }
+            // the last leg takes the rounding dust
+            let leg_shares = if Some(i) == last {
+                remaining
+            } else {
+                shares.mul_div(*weight, total_weight).ok_or_else(overflow)?
+            };
+            remaining = remaining.checked_sub(leg_shares).ok_or_else(overflow)?;
+            legs.push((address.to_string(), leg_shares));
+        }
+        self.apply_basket(legs, operation)
+    }
+
+    fn apply_basket(
+        &mut self,
+        mut pending: Vec<(String, Num)>,
+        operation: MpOperation,
+    ) -> Result<BasketOutcome, MpError> {
+        let mut pool = self.clone();
+        let mut outcome = BasketOutcome {
+            shares: Num::ZERO,
+            legs: Vec::new(),
+        };
+        while !pending.is_empty() {
+            let mut best: Option<(usize, MpPool, BasketLeg, SNum)> = None;
+            let mut first_error = None;
+            for (i, (address, amount)) in pending.iter().enumerate() {
+                let mut candidate = pool.clone();
+                let leg = match candidate.apply_leg(address, *amount, operation) {
+                    Ok(leg) => leg,
+                    Err(e) => {
+                        first_error.get_or_insert(e);
+                        continue;
+                    }
+                };
+                let cost = leg.cost(candidate.assets[address.as_str()].price);
+                if best
+                    .as_ref()
+                    .is_none_or(|(_, _, _, best_cost)| cost < *best_cost)
+                {
+                    best = Some((i, candidate, leg, cost));
+                }
+            }
+            let Some((i, candidate, leg, _)) = best else {
+                return Err(first_error.expect("pending legs are not empty"));
+            };
+            pending.remove(i);
+            pool = candidate;
+            outcome.shares = outcome
+                .shares
+                .checked_add(leg.shares)
+                .ok_or(MpError::Overflow { operation })?;
+            outcome.legs.push(leg);
+        }
+        *self = pool;
+        Ok(outcome)
+    }
+
+    pub(super) fn apply_leg(
+        &mut self,
+        address: &str,
+        amount: Num,
+        operation: MpOperation,
+    ) -> Result<BasketLeg, MpError> {
+        let before = self.asset(address, operation)?.clone();
+        let (quantity, shares) = match operation {
+            MpOperation::Mint => (amount, self.mint(address, amount)?),
+            _ => (self.burn(address, amount)?, amount),
+        };
+        let after = &self.assets[address];
+        Ok(BasketLeg::new(address, &before, after, quantity, shares))
+    }
+}
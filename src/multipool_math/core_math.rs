@@ -1,455 +1,992 @@
-use crate::num::{num::Num, snum::SNum};
+use super::solver::{
+    burn_fee_residual, mint_fee_residual, solve_burn_numerically, solve_mint_numerically,
+    CurveBranch, CurveRoot, CurveSolution, Residual,
+};
+use crate::num::{
+    num::Num,
+    numeric::{Checked, PoolNumeric},
+};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
 
 #[derive(Clone, PartialEq, Eq, Debug)]
-pub struct MpContext {
-    pub total_current_usd_amount: Num,
-    pub total_asset_percents: Num,
-    pub curve_coef: Num,
-    pub deviation_percent_limit: Num,
-    pub operation_base_fee: Num,
-    pub user_cashback_balance: Num,
+pub struct MpContextOf<N> {
+    pub total_current_usd_amount: N,
+    pub total_asset_percents: N,
+    pub curve_coef: N,
+    pub deviation_percent_limit: N,
+    pub operation_base_fee: N,
+    pub user_cashback_balance: N,
 }
 
+pub type MpContext = MpContextOf<Num>;
+
 #[derive(Clone, PartialEq, Eq, Debug)]
-pub struct MpAsset {
-    pub quantity: Num,
-    pub price: Num,
-    pub collected_fees: Num,
-    pub collected_cashbacks: Num,
-    pub percent: Num,
+pub struct MpAssetOf<N> {
+    pub quantity: N,
+    pub price: N,
+    pub collected_fees: N,
+    pub collected_cashbacks: N,
+    pub percent: N,
+}
+
+pub type MpAsset = MpAssetOf<Num>;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+pub enum MpOperation {
+    Mint,
+    MintRev,
+    Burn,
+    BurnRev,
+    Reconfigure,
+    Payout,
+    Valuation,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
-pub enum MpError {
-    NoCurveSolutions,
-    DeviationBiggerThanLimit,
-    InsufficientBurnQuantity,
+pub enum MpErrorOf<N> {
+    NoCurveSolutions {
+        operation: MpOperation,
+        requested: N,
+    },
+    DeviationBiggerThanLimit {
+        operation: MpOperation,
+        deviation: N,
+        limit: N,
+    },
+    InsufficientBurnQuantity {
+        operation: MpOperation,
+        requested: N,
+        available: N,
+    },
+    ZeroPrice {
+        operation: MpOperation,
+    },
+    ZeroPercent {
+        operation: MpOperation,
+    },
+    Overflow {
+        operation: MpOperation,
+    },
+    UnknownAsset {
+        operation: MpOperation,
+        address: String,
+    },
+    InsufficientShares {
+        operation: MpOperation,
+        requested: N,
+        available: N,
+    },
+    EmptyBasket {
+        operation: MpOperation,
+    },
+    AssetAlreadyListed {
+        operation: MpOperation,
+        address: String,
+    },
+    ZeroAmount {
+        operation: MpOperation,
+    },
+}
+
+pub type MpError = MpErrorOf<Num>;
+
+impl Display for MpOperation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MpOperation::Mint => write!(f, "mint"),
+            MpOperation::MintRev => write!(f, "reversed mint"),
+            MpOperation::Burn => write!(f, "burn"),
+            MpOperation::BurnRev => write!(f, "reversed burn"),
+            MpOperation::Reconfigure => write!(f, "reconfiguration"),
+            MpOperation::Payout => write!(f, "payout"),
+            MpOperation::Valuation => write!(f, "valuation"),
+        }
+    }
+}
+
+impl<N: Display> Display for MpErrorOf<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MpErrorOf::NoCurveSolutions {
+                operation,
+                requested,
+            } => write!(f, "{operation} of {requested}: fee curve has no solutions"),
+            MpErrorOf::DeviationBiggerThanLimit {
+                operation,
+                deviation,
+                limit,
+            } => write!(
+                f,
+                "{operation}: deviation {deviation} is bigger than limit {limit}"
+            ),
+            MpErrorOf::InsufficientBurnQuantity {
+                operation,
+                requested,
+                available,
+            } => write!(
+                f,
+                "{operation}: requested {requested} while only {available} is available"
+            ),
+            MpErrorOf::ZeroPrice { operation } => write!(f, "{operation}: asset price is zero"),
+            MpErrorOf::ZeroPercent { operation } => {
+                write!(f, "{operation}: asset target percent is zero")
+            }
+            MpErrorOf::Overflow { operation } => write!(f, "{operation}: arithmetic overflow"),
+            MpErrorOf::UnknownAsset { operation, address } => {
+                write!(f, "{operation}: asset {address} is not in the pool")
+            }
+            MpErrorOf::InsufficientShares {
+                operation,
+                requested,
+                available,
+            } => write!(
+                f,
+                "{operation}: requested {requested} shares while only {available} are issued"
+            ),
+            MpErrorOf::EmptyBasket { operation } => {
+                write!(f, "{operation}: basket has no weighted assets")
+            }
+            MpErrorOf::AssetAlreadyListed { operation, address } => {
+                write!(f, "{operation}: asset {address} is already in the pool")
+            }
+            MpErrorOf::ZeroAmount { operation } => write!(f, "{operation}: traded amount is zero"),
+        }
+    }
 }
 
-pub struct MpContextSigned {
-    pub total_current_usd_amount: SNum,
-    pub total_asset_percents: SNum,
-    pub curve_coef: SNum,
-    pub deviation_percent_limit: SNum,
-    pub operation_base_fee: SNum,
-    pub user_cashback_balance: SNum,
+impl<N: Display + std::fmt::Debug> std::error::Error for MpErrorOf<N> {}
+
+pub struct MpContextSigned<N: PoolNumeric = Num> {
+    pub total_current_usd_amount: N::Signed,
+    pub total_asset_percents: N::Signed,
+    pub curve_coef: N::Signed,
+    pub deviation_percent_limit: N::Signed,
+    pub operation_base_fee: N::Signed,
+    pub user_cashback_balance: N::Signed,
 }
 
-pub struct MpAssetSigned {
-    pub quantity: SNum,
-    pub price: SNum,
-    pub collected_fees: SNum,
-    pub collected_cashbacks: SNum,
-    pub percent: SNum,
+pub struct MpAssetSigned<N: PoolNumeric = Num> {
+    pub quantity: N::Signed,
+    pub price: N::Signed,
+    pub collected_fees: N::Signed,
+    pub collected_cashbacks: N::Signed,
+    pub percent: N::Signed,
 }
 
-impl MpAsset {
-    pub fn sign(&self) -> MpAssetSigned {
+impl<N: PoolNumeric> MpAssetOf<N> {
+    pub fn sign(&self) -> MpAssetSigned<N> {
         MpAssetSigned {
-            quantity: self.quantity.into(),
-            price: self.price.into(),
-            collected_cashbacks: self.collected_cashbacks.into(),
-            collected_fees: self.collected_fees.into(),
-            percent: self.percent.into(),
+            quantity: self.quantity.to_signed(),
+            price: self.price.to_signed(),
+            collected_cashbacks: self.collected_cashbacks.to_signed(),
+            collected_fees: self.collected_fees.to_signed(),
+            percent: self.percent.to_signed(),
         }
     }
 }
 
-impl MpContext {
-    pub fn sign(&self) -> MpContextSigned {
+impl<N: PoolNumeric> MpContextOf<N> {
+    pub fn sign(&self) -> MpContextSigned<N> {
         MpContextSigned {
-            total_current_usd_amount: self.total_current_usd_amount.into(),
-            total_asset_percents: self.total_asset_percents.into(),
-            curve_coef: self.curve_coef.into(),
-            deviation_percent_limit: self.deviation_percent_limit.into(),
-            operation_base_fee: self.operation_base_fee.into(),
-            user_cashback_balance: self.user_cashback_balance.into(),
+            total_current_usd_amount: self.total_current_usd_amount.to_signed(),
+            total_asset_percents: self.total_asset_percents.to_signed(),
+            curve_coef: self.curve_coef.to_signed(),
+            deviation_percent_limit: self.deviation_percent_limit.to_signed(),
+            operation_base_fee: self.operation_base_fee.to_signed(),
+            user_cashback_balance: self.user_cashback_balance.to_signed(),
+        }
+    }
+}
+
+impl MpAsset {
+    /// Converts the asset to another number backend.
+    pub fn to_numeric<M: PoolNumeric>(&self) -> MpAssetOf<M> {
+        MpAssetOf {
+            quantity: M::from_num(self.quantity),
+            price: M::from_num(self.price),
+            collected_fees: M::from_num(self.collected_fees),
+            collected_cashbacks: M::from_num(self.collected_cashbacks),
+            percent: M::from_num(self.percent),
+        }
+    }
+}
+
+impl MpContext {
+    /// Converts the context to another number backend.
+    pub fn to_numeric<M: PoolNumeric>(&self) -> MpContextOf<M> {
+        MpContextOf {
+            total_current_usd_amount: M::from_num(self.total_current_usd_amount),
+            total_asset_percents: M::from_num(self.total_asset_percents),
+            curve_coef: M::from_num(self.curve_coef),
+            deviation_percent_limit: M::from_num(self.deviation_percent_limit),
+            operation_base_fee: M::from_num(self.operation_base_fee),
+            user_cashback_balance: M::from_num(self.user_cashback_balance),
         }
     }
 }
 
-pub fn get_utilisable_mint_quantity(
-    supplied_quantity: SNum,
-    asset: &MpAssetSigned,
-    context: &MpContextSigned,
-) -> SNum {
-    let mut utilisable_quantity = SNum::ZERO;
-    let bf = SNum::from("1") + context.operation_base_fee;
-    let m = SNum::from("1") - asset.percent / context.total_asset_percents;
-    let cp = context.curve_coef / context.deviation_percent_limit;
+pub fn get_utilisable_mint_quantity<N: PoolNumeric>(
+    supplied_quantity: N::Signed,
+    asset: &MpAssetSigned<N>,
+    context: &MpContextSigned<N>,
+) -> N::Signed {
+    closed_form_mint_quantity(supplied_quantity, asset, context)
+        .ok()
+        .flatten()
+        .map(|(quantity, _, _)| quantity)
+        .unwrap_or_else(|| N::zero().to_signed())
+}
+
+/// Closed form root of the mint fee equation, `None` if neither branch has
+/// one. Fails if an intermediate overflows.
+fn closed_form_mint_quantity<N: PoolNumeric>(
+    supplied_quantity: N::Signed,
+    asset: &MpAssetSigned<N>,
+    context: &MpContextSigned<N>,
+) -> Result<Option<(N::Signed, CurveBranch, CurveRoot)>, MpErrorOf<N>> {
+    let overflow = || MpErrorOf::Overflow {
+        operation: MpOperation::Mint,
+    };
+    let value = |checked: Checked<N::Signed>| checked.get().ok_or_else(overflow);
+    let num = |value: &N::Signed| Checked::new(value.clone());
+    let zero = N::zero().to_signed();
+    let one = num(&N::one().to_signed());
+    let two = num(&N::from_decimal("2").to_signed());
+    let four = num(&N::from_decimal("4").to_signed());
+    let supplied = num(&supplied_quantity);
+    let quantity = num(&asset.quantity);
+    let price = num(&asset.price);
+    let total = num(&context.total_current_usd_amount);
+    let limit = num(&context.deviation_percent_limit);
+
+    // assets with a zero target can't be minted
+    if context.total_asset_percents == zero || asset.percent == zero {
+        return Ok(None);
+    }
+    // nothing may deviate, leave it to the solver
+    if context.deviation_percent_limit == zero {
+        return Ok(None);
+    }
+    let mut utilisable_quantity = None;
+    let bf = one.clone() + num(&context.operation_base_fee);
+    let m = value(one.clone() - num(&asset.percent) / num(&context.total_asset_percents))?;
+    // the only weighted asset has no branch switch, leave it to the solver
+    if m == zero {
+        return Ok(None);
+    }
+    let m = num(&m);
+    let cp = num(&context.curve_coef) / limit.clone();
+
+    let cmp = value(
+        -(quantity.clone() * price.clone() + total.clone() * (m.clone() - one.clone()))
+            / (m.clone() * price.clone()),
+    )?;
 
     {
-        let dlm = context.deviation_percent_limit - m;
-        let t = asset.quantity * asset.price - context.total_current_usd_amount;
-        let a = (bf * dlm + cp * m) * asset.price;
-        let b = dlm * (context.total_current_usd_amount * bf - supplied_quantity * asset.price)
-            - (bf - cp) * t
-            + cp * m * context.total_current_usd_amount;
-        let c = t * supplied_quantity - dlm * context.total_current_usd_amount * supplied_quantity;
-
-        let d = b.pow2() - SNum::from("4") * a * c;
-
-        let cmp = -(asset.quantity * asset.price
-            + context.total_current_usd_amount * (m - SNum::from("1")))
-            / (m * asset.price);
-
-        if d >= SNum::ZERO {
-            let d: SNum = d.sqrt().into();
-            let x1 = (-b - d) / SNum::from("2") / a;
-            let x2 = (-b + d) / SNum::from("2") / a;
-
-            if x1 > cmp && x1 > SNum::ZERO && x1 < supplied_quantity {
-                utilisable_quantity = x1;
+        let dlm = limit.clone() - m.clone();
+        let t = quantity.clone() * price.clone() - total.clone();
+        let a = (bf.clone() * dlm.clone() + cp.clone() * m.clone()) * price.clone();
+        let b = dlm.clone() * (total.clone() * bf.clone() - supplied.clone() * price.clone())
+            - (bf.clone() - cp.clone()) * t.clone()
+            + cp.clone() * m.clone() * total.clone();
+        let c = t * supplied.clone() - dlm * total.clone() * supplied.clone();
+
+        let d = value(b.clone() * b.clone() - four.clone() * a.clone() * c)?;
+
+        if d >= zero && value(a.clone())? != zero {
+            let d = num(&N::abs(&d).sqrt().to_signed());
+            let x1 = value((-b.clone() - d.clone()) / two.clone() / a.clone())?;
+            let x2 = value((-b + d) / two.clone() / a)?;
+
+            if x1 > cmp && x1 > zero && x1 < supplied_quantity {
+                utilisable_quantity = Some((x1, CurveBranch::AboveTarget, CurveRoot::Minus));
             }
-            if x2 > cmp && x2 > SNum::ZERO && x2 < supplied_quantity {
-                utilisable_quantity = x2;
+            if x2 > cmp && x2 > zero && x2 < supplied_quantity {
+                utilisable_quantity = Some((x2, CurveBranch::AboveTarget, CurveRoot::Plus));
             }
         }
     }
 
     {
-        let dlm = context.deviation_percent_limit + m;
-        let t = asset.quantity * asset.price - context.total_current_usd_amount;
-        let a = (bf * dlm - cp * m) * asset.price;
-        let b = dlm * (context.total_current_usd_amount * bf - supplied_quantity * asset.price)
-            + (bf - cp) * t
-            - cp * m * context.total_current_usd_amount;
-        let c = -t * supplied_quantity - dlm * context.total_current_usd_amount * supplied_quantity;
-
-        let d = b.pow2() - SNum::from("4") * a * c;
-
-        let cmp = -(asset.quantity * asset.price
-            + context.total_current_usd_amount * (m - SNum::from("1")))
-            / (m * asset.price);
-
-        if d >= SNum::ZERO {
-            let d: SNum = d.sqrt().into();
-            let x1 = (-b - d) / SNum::from("2") / a;
-            let x2 = (-b + d) / SNum::from("2") / a;
-
-            if x1 < cmp && x1 > SNum::ZERO && x1 < supplied_quantity {
-                utilisable_quantity = x1;
+        let dlm = limit + m.clone();
+        let t = quantity * price.clone() - total.clone();
+        let a = (bf.clone() * dlm.clone() - cp.clone() * m.clone()) * price.clone();
+        let b = dlm.clone() * (total.clone() * bf.clone() - supplied.clone() * price)
+            + (bf - cp.clone()) * t.clone()
+            - cp * m * total.clone();
+        let c = -t * supplied.clone() - dlm * total * supplied;
+
+        let d = value(b.clone() * b.clone() - four * a.clone() * c)?;
+
+        if d >= zero && value(a.clone())? != zero {
+            let d = num(&N::abs(&d).sqrt().to_signed());
+            let x1 = value((-b.clone() - d.clone()) / two.clone() / a.clone())?;
+            let x2 = value((-b + d) / two / a)?;
+
+            if x1 < cmp && x1 > zero && x1 < supplied_quantity {
+                utilisable_quantity = Some((x1, CurveBranch::BelowTarget, CurveRoot::Minus));
             }
-            if x2 < cmp && x2 > SNum::ZERO && x2 < supplied_quantity {
-                utilisable_quantity = x2;
+            if x2 < cmp && x2 > zero && x2 < supplied_quantity {
+                utilisable_quantity = Some((x2, CurveBranch::BelowTarget, CurveRoot::Plus));
             }
         }
     }
 
-    return utilisable_quantity;
+    Ok(utilisable_quantity)
+}
+
+pub fn get_suppliable_burn_quantity<N: PoolNumeric>(
+    utilisable_quantity: N::Signed,
+    asset: &MpAssetSigned<N>,
+    context: &MpContextSigned<N>,
+) -> N::Signed {
+    closed_form_burn_quantity(utilisable_quantity, asset, context)
+        .ok()
+        .flatten()
+        .map(|(quantity, _, _)| quantity)
+        .unwrap_or_else(|| N::zero().to_signed())
 }
 
-pub fn get_suppliable_burn_quantity(
-    utilisable_quantity: SNum,
-    asset: &MpAssetSigned,
-    context: &MpContextSigned,
-) -> SNum {
-    let mut suppliable_quantity = SNum::ZERO;
-    let bf = SNum::from("1") + context.operation_base_fee;
-    let m = SNum::from("1") - asset.percent / context.total_asset_percents;
-    let cp = context.curve_coef / context.deviation_percent_limit;
+/// Closed form root of the burn fee equation, `None` if neither branch has
+/// one. Fails if an intermediate overflows.
+fn closed_form_burn_quantity<N: PoolNumeric>(
+    utilisable_quantity: N::Signed,
+    asset: &MpAssetSigned<N>,
+    context: &MpContextSigned<N>,
+) -> Result<Option<(N::Signed, CurveBranch, CurveRoot)>, MpErrorOf<N>> {
+    let overflow = || MpErrorOf::Overflow {
+        operation: MpOperation::BurnRev,
+    };
+    let value = |checked: Checked<N::Signed>| checked.get().ok_or_else(overflow);
+    let num = |value: &N::Signed| Checked::new(value.clone());
+    let zero = N::zero().to_signed();
+    let one = num(&N::one().to_signed());
+    let two = num(&N::from_decimal("2").to_signed());
+    let four = num(&N::from_decimal("4").to_signed());
+    let utilisable = num(&utilisable_quantity);
+    let price = num(&asset.price);
+    let total = num(&context.total_current_usd_amount);
+    let limit = num(&context.deviation_percent_limit);
+
+    if context.total_asset_percents == zero || context.deviation_percent_limit == zero {
+        return Ok(None);
+    }
+    let mut suppliable_quantity: Option<(N::Signed, CurveBranch, CurveRoot)> = None;
+    let bf = one.clone() + num(&context.operation_base_fee);
+    let m = value(one - num(&asset.percent) / num(&context.total_asset_percents))?;
+    if m == zero {
+        return Ok(None);
+    }
+    let m = num(&m);
+    let cp = num(&context.curve_coef) / limit.clone();
+
+    let t = num(&asset.quantity) * price.clone() - total.clone();
+    let cmp = value((t.clone() + m.clone() * total.clone()) / (m.clone() * price.clone()))?;
+    // deviation after burning `x` stays within the limit
+    let within_limit = |x: &N::Signed| {
+        (m.clone() + t.clone() / (total.clone() - num(x) * price.clone()))
+            .get()
+            .is_some_and(|deviation| {
+                N::abs(&deviation) < N::abs(&context.deviation_percent_limit)
+            })
+    };
+    let smaller = |current: &Option<(N::Signed, CurveBranch, CurveRoot)>, x: &N::Signed| {
+        current.as_ref().is_none_or(|(q, _, _)| q > x || *q == zero)
+    };
 
     {
-        let dlm = context.deviation_percent_limit - m;
-        let t = asset.quantity * asset.price - context.total_current_usd_amount;
-        let a = -dlm * asset.price;
-        let b = ((bf * asset.price * utilisable_quantity) + context.total_current_usd_amount) * dlm
-            + cp * m * asset.price * utilisable_quantity
-            - t;
-        let c = -bf * context.total_current_usd_amount * utilisable_quantity * dlm
-            + t * utilisable_quantity * (bf - cp)
-            - cp * m * context.total_current_usd_amount * utilisable_quantity;
-
-        let cmp = (t + m * context.total_current_usd_amount) / (m * asset.price);
-
-        let d = b.pow2() - SNum::from("4") * a * c;
-
-        if d > SNum::ZERO {
-            let d: SNum = d.sqrt().into();
-            let x1 = (-b - d) / SNum::from("2") / a;
-            let x2 = (-b + d) / SNum::from("2") / a;
-
-            if (m + t / (context.total_current_usd_amount - x1 * asset.price)).abs()
-                < context.deviation_percent_limit.abs()
-                && x1 < cmp
-            {
-                suppliable_quantity = if suppliable_quantity > x1 || suppliable_quantity.is_zero() {
-                    x1
-                } else {
-                    suppliable_quantity
-                };
+        let dlm = limit.clone() - m.clone();
+        let a = -dlm.clone() * price.clone();
+        let b = ((bf.clone() * price.clone() * utilisable.clone()) + total.clone())
+            * dlm.clone()
+            + cp.clone() * m.clone() * price.clone() * utilisable.clone()
+            - t.clone();
+        let c = -bf.clone() * total.clone() * utilisable.clone() * dlm
+            + t.clone() * utilisable.clone() * (bf.clone() - cp.clone())
+            - cp.clone() * m.clone() * total.clone() * utilisable.clone();
+
+        let d = value(b.clone() * b.clone() - four.clone() * a.clone() * c)?;
+
+        if d > zero && value(a.clone())? != zero {
+            let d = num(&N::abs(&d).sqrt().to_signed());
+            let x1 = value((-b.clone() - d.clone()) / two.clone() / a.clone())?;
+            let x2 = value((-b + d) / two.clone() / a)?;
+
+            if within_limit(&x1) && x1 < cmp && smaller(&suppliable_quantity, &x1) {
+                suppliable_quantity = Some((x1, CurveBranch::AboveTarget, CurveRoot::Minus));
             }
-            if (m + t / (context.total_current_usd_amount - x2 * asset.price)).abs()
-                < context.deviation_percent_limit.abs()
-                && x2 < cmp
-            {
-                suppliable_quantity = if suppliable_quantity > x2 || suppliable_quantity.is_zero() {
-                    x2
-                } else {
-                    suppliable_quantity
-                };
+            if within_limit(&x2) && x2 < cmp && smaller(&suppliable_quantity, &x2) {
+                suppliable_quantity = Some((x2, CurveBranch::AboveTarget, CurveRoot::Plus));
             }
         }
     }
 
     {
-        let dlm = context.deviation_percent_limit + m;
-        let t = asset.quantity * asset.price - context.total_current_usd_amount;
-        let a = dlm * asset.price;
-        let b = -((bf * asset.price * utilisable_quantity) + context.total_current_usd_amount)
-            * dlm
-            + cp * m * asset.price * utilisable_quantity
-            - t;
-        let c = bf * context.total_current_usd_amount * utilisable_quantity * dlm
-            + t * utilisable_quantity * (bf - cp)
-            - cp * m * context.total_current_usd_amount * utilisable_quantity;
-
-        let cmp = (t + m * context.total_current_usd_amount) / (m * asset.price);
-
-        let d = b.pow2() - SNum::from("4") * a * c;
-
-        if d > SNum::ZERO {
-            let d: SNum = d.sqrt().into();
-            let x1 = (-b - d) / SNum::from("2") / a;
-            let x2 = (-b + d) / SNum::from("2") / a;
-
-            if (m + t / (context.total_current_usd_amount - x1 * asset.price)).abs()
-                < context.deviation_percent_limit.abs()
-                && x1 > cmp
-            {
-                suppliable_quantity = if suppliable_quantity > x1 || suppliable_quantity.is_zero() {
-                    x1
-                } else {
-                    suppliable_quantity
-                };
+        let dlm = limit + m.clone();
+        let a = dlm.clone() * price.clone();
+        let b = -((bf.clone() * price.clone() * utilisable.clone()) + total.clone())
+            * dlm.clone()
+            + cp.clone() * m.clone() * price.clone() * utilisable.clone()
+            - t.clone();
+        let c = bf.clone() * total.clone() * utilisable.clone() * dlm
+            + t.clone() * utilisable.clone() * (bf - cp.clone())
+            - cp * m.clone() * total.clone() * utilisable;
+
+        let d = value(b.clone() * b.clone() - four * a.clone() * c)?;
+
+        if d > zero && value(a.clone())? != zero {
+            let d = num(&N::abs(&d).sqrt().to_signed());
+            let x1 = value((-b.clone() - d.clone()) / two.clone() / a.clone())?;
+            let x2 = value((-b + d) / two / a)?;
+
+            if within_limit(&x1) && x1 > cmp && smaller(&suppliable_quantity, &x1) {
+                suppliable_quantity = Some((x1, CurveBranch::BelowTarget, CurveRoot::Minus));
             }
-            if (m + t / (context.total_current_usd_amount - x2 * asset.price)).abs()
-                < context.deviation_percent_limit.abs()
-                && x2 > cmp
-            {
-                suppliable_quantity = if suppliable_quantity > x2 || suppliable_quantity.is_zero() {
-                    x2
-                } else {
-                    suppliable_quantity
-                };
+            if within_limit(&x2) && x2 > cmp && smaller(&suppliable_quantity, &x2) {
+                suppliable_quantity = Some((x2, CurveBranch::BelowTarget, CurveRoot::Plus));
             }
         }
     }
 
-    return suppliable_quantity;
+    Ok(suppliable_quantity)
 }
 
-pub fn calculate_deviation_mint(
-    utilisable_quantity: Num,
-    asset: &MpAsset,
-    context: &MpContext,
-) -> Num {
-    let share: SNum = ((asset.quantity + utilisable_quantity) * asset.price
-        / (context.total_current_usd_amount + utilisable_quantity * asset.price))
-        .into();
-    let ideal_share: SNum = (asset.percent / context.total_asset_percents).into();
-    return (share - ideal_share).abs();
+/// Relative residual accepted for closed form roots before falling back to
+/// the numerical solver.
+const CLOSED_FORM_TOLERANCE: &str = "0.000000001";
+
+/// Absolute residual floor, covers rounding of tiny quantities.
+const CLOSED_FORM_MIN_RESIDUAL: &str = "0.000000000000000000001";
+
+fn within_tolerance<N: PoolNumeric>(residual: Residual<N>, scale: &N) -> bool {
+    match residual {
+        Residual::Finite(value) => {
+            N::abs(&value)
+                <= scale.clone() * N::from_decimal(CLOSED_FORM_TOLERANCE)
+                    + N::from_decimal(CLOSED_FORM_MIN_RESIDUAL)
+        }
+        Residual::PositiveInfinity | Residual::NegativeInfinity => false,
+    }
 }
 
-pub fn calculate_deviation_burn(
-    supplied_quantity: Num,
-    asset: &MpAsset,
-    context: &MpContext,
-) -> Num {
-    let share: SNum = ((asset.quantity - supplied_quantity) * asset.price
-        / (context.total_current_usd_amount - supplied_quantity * asset.price))
-        .into();
-    let ideal_share: SNum = (asset.percent / context.total_asset_percents).into();
-    return (share - ideal_share).abs();
+pub fn get_utilisable_mint_solution<N: PoolNumeric>(
+    supplied_quantity: N,
+    asset: &MpAssetOf<N>,
+    context: &MpContextOf<N>,
+) -> Result<Option<CurveSolution<N>>, MpErrorOf<N>> {
+    let closed_form = closed_form_mint_quantity(
+        supplied_quantity.to_signed(),
+        &asset.sign(),
+        &context.sign(),
+    )?
+    .map(|(quantity, branch, root)| CurveSolution {
+        quantity: N::abs(&quantity),
+        branch,
+        root,
+    })
+    .filter(|solution| {
+        let residual = mint_fee_residual(
+            solution.quantity.clone(),
+            supplied_quantity.clone(),
+            asset,
+            context,
+        );
+        within_tolerance(residual, &supplied_quantity)
+    });
+    Ok(closed_form.or_else(|| solve_mint_numerically(supplied_quantity, asset, context)))
 }
 
-impl MpContext {
+pub fn get_suppliable_burn_solution<N: PoolNumeric>(
+    utilisable_quantity: N,
+    asset: &MpAssetOf<N>,
+    context: &MpContextOf<N>,
+) -> Result<Option<CurveSolution<N>>, MpErrorOf<N>> {
+    let closed_form = closed_form_burn_quantity(
+        utilisable_quantity.to_signed(),
+        &asset.sign(),
+        &context.sign(),
+    )?
+    .map(|(quantity, branch, root)| CurveSolution {
+        quantity: N::abs(&quantity),
+        branch,
+        root,
+    })
+    .filter(|solution| {
+        solution.quantity <= asset.quantity && {
+            let residual = burn_fee_residual(
+                solution.quantity.clone(),
+                utilisable_quantity.clone(),
+                asset,
+                context,
+            );
+            within_tolerance(residual, &utilisable_quantity)
+        }
+    });
+    Ok(closed_form.or_else(|| solve_burn_numerically(utilisable_quantity, asset, context)))
+}
+
+pub fn calculate_deviation_mint<N: PoolNumeric>(
+    utilisable_quantity: N,
+    asset: &MpAssetOf<N>,
+    context: &MpContextOf<N>,
+) -> N {
+    let share = ((asset.quantity.clone() + utilisable_quantity.clone()) * asset.price.clone()
+        / (context.total_current_usd_amount.clone() + utilisable_quantity * asset.price.clone()))
+    .to_signed();
+    let ideal_share = (asset.percent.clone() / context.total_asset_percents.clone()).to_signed();
+    return N::abs(&(share - ideal_share));
+}
+
+pub fn calculate_deviation_burn<N: PoolNumeric>(
+    supplied_quantity: N,
+    asset: &MpAssetOf<N>,
+    context: &MpContextOf<N>,
+) -> N {
+    let share = ((asset.quantity.clone() - supplied_quantity.clone()) * asset.price.clone()
+        / (context.total_current_usd_amount.clone() - supplied_quantity * asset.price.clone()))
+    .to_signed();
+    let ideal_share = (asset.percent.clone() / context.total_asset_percents.clone()).to_signed();
+    return N::abs(&(share - ideal_share));
+}
+
+impl<N: PoolNumeric> MpContextOf<N> {
+    fn check_operands(
+        &self,
+        asset: &MpAssetOf<N>,
+        operation: MpOperation,
+    ) -> Result<(), MpErrorOf<N>> {
+        if asset.price.is_zero() {
+            return Err(MpErrorOf::ZeroPrice { operation });
+        }
+        let minting = matches!(operation, MpOperation::Mint | MpOperation::MintRev);
+        if self.total_asset_percents.is_zero() || minting && asset.percent.is_zero() {
+            return Err(MpErrorOf::ZeroPercent { operation });
+        }
+        Ok(())
+    }
+
+    /// Cashback paid out of `collected_cashbacks` for moving the deviation
+    /// from `deviation_old` down to `deviation_new`.
+    fn cashback(&self, asset: &MpAssetOf<N>, deviation_old: &N, deviation_new: &N) -> N {
+        if !deviation_old.is_zero() {
+            asset.collected_cashbacks.clone() * (deviation_old.clone() - deviation_new.clone())
+                / deviation_old.clone()
+        } else {
+            N::zero()
+        }
+    }
+
+    /// Fee charged on `quantity` for moving the deviation to `deviation`,
+    /// `None` on overflow.
+    fn deviation_fee(&self, deviation: &N, quantity: &N) -> Option<N> {
+        self.curve_coef
+            .checked_mul(deviation)?
+            .checked_mul(quantity)?
+            .checked_div(&self.deviation_percent_limit)?
+            .checked_div(&self.deviation_percent_limit.checked_sub(deviation)?)
+    }
+
+    /// Applies `operation` to copies of the context and the asset, which
+    /// replace the originals only if it succeeds.
+    fn atomically<F>(&mut self, asset: &mut MpAssetOf<N>, operation: F) -> Result<N, MpErrorOf<N>>
+    where
+        F: FnOnce(&mut Self, &mut MpAssetOf<N>) -> Result<N, MpErrorOf<N>>,
+    {
+        let mut context = self.clone();
+        let mut updated = asset.clone();
+        let quantity = operation(&mut context, &mut updated)?;
+        *self = context;
+        *asset = updated;
+        Ok(quantity)
+    }
+
     pub fn mint_rev(
         &mut self,
-        asset: &mut MpAsset,
-        utilisable_quantity: Num,
-    ) -> Result<Num, MpError> {
+        asset: &mut MpAssetOf<N>,
+        utilisable_quantity: N,
+    ) -> Result<N, MpErrorOf<N>> {
+        self.atomically(asset, |context, asset| {
+            context.mint_rev_in_place(asset, utilisable_quantity)
+        })
+    }
+
+    fn mint_rev_in_place(
+        &mut self,
+        asset: &mut MpAssetOf<N>,
+        utilisable_quantity: N,
+    ) -> Result<N, MpErrorOf<N>> {
         let context = self;
+        let operation = MpOperation::MintRev;
+        context.check_operands(asset, operation)?;
+        let overflow = || MpErrorOf::Overflow { operation };
+
+        let usd_amount = utilisable_quantity
+            .checked_mul(&asset.price)
+            .ok_or_else(overflow)?;
+        let quantity = asset
+            .quantity
+            .checked_add(&utilisable_quantity)
+            .ok_or_else(overflow)?;
         if context.total_current_usd_amount.is_zero() {
-            context.total_current_usd_amount = utilisable_quantity * asset.price;
-            asset.quantity += utilisable_quantity;
+            context.total_current_usd_amount = usd_amount;
+            asset.quantity = quantity;
             return Ok(utilisable_quantity);
         }
+        let total_current_usd_amount = context
+            .total_current_usd_amount
+            .checked_add(&usd_amount)
+            .ok_or_else(overflow)?;
+        let base_fee = utilisable_quantity
+            .checked_mul(&context.operation_base_fee)
+            .ok_or_else(overflow)?;
         let supplied_quantity;
-        let deviation_new = calculate_deviation_mint(utilisable_quantity, asset, context);
-        let deviation_old = calculate_deviation_mint(Num::ZERO, asset, context);
+        let deviation_new = calculate_deviation_mint(utilisable_quantity.clone(), asset, context);
+        let deviation_old = calculate_deviation_mint(N::zero(), asset, context);
 
         if deviation_new <= deviation_old {
-            let cashback = if !deviation_old.is_zero() {
-                asset.collected_cashbacks * (deviation_old - deviation_new) / deviation_old
-            } else {
-                Num::ZERO
-            };
-            asset.collected_cashbacks -= cashback;
-            context.user_cashback_balance += cashback;
-            supplied_quantity =
-                utilisable_quantity + utilisable_quantity * context.operation_base_fee;
+            let cashback = context.cashback(asset, &deviation_old, &deviation_new);
+            asset.collected_cashbacks = asset
+                .collected_cashbacks
+                .checked_sub(&cashback)
+                .ok_or_else(overflow)?;
+            context.user_cashback_balance = context
+                .user_cashback_balance
+                .checked_add(&cashback)
+                .ok_or_else(overflow)?;
+            supplied_quantity = utilisable_quantity
+                .checked_add(&base_fee)
+                .ok_or_else(overflow)?;
         } else {
             if deviation_new > context.deviation_percent_limit {
-                return Err(MpError::DeviationBiggerThanLimit);
+                return Err(MpErrorOf::DeviationBiggerThanLimit {
+                    operation,
+                    deviation: deviation_new,
+                    limit: context.deviation_percent_limit.clone(),
+                });
             }
 
-            let collected_deviation_fee = context.curve_coef * deviation_new * utilisable_quantity
-                / context.deviation_percent_limit
-                / (context.deviation_percent_limit - deviation_new);
-            asset.collected_cashbacks += collected_deviation_fee;
+            let collected_deviation_fee = context
+                .deviation_fee(&deviation_new, &utilisable_quantity)
+                .ok_or_else(overflow)?;
+            asset.collected_cashbacks = asset
+                .collected_cashbacks
+                .checked_add(&collected_deviation_fee)
+                .ok_or_else(overflow)?;
             supplied_quantity = utilisable_quantity
-                + utilisable_quantity * context.operation_base_fee
-                + collected_deviation_fee;
+                .checked_add(&base_fee)
+                .and_then(|quantity| quantity.checked_add(&collected_deviation_fee))
+                .ok_or_else(overflow)?;
         }
-        asset.quantity += utilisable_quantity;
-        context.total_current_usd_amount += utilisable_quantity * asset.price;
-        asset.collected_fees += utilisable_quantity * context.operation_base_fee;
+        asset.quantity = quantity;
+        context.total_current_usd_amount = total_current_usd_amount;
+        asset.collected_fees = asset
+            .collected_fees
+            .checked_add(&base_fee)
+            .ok_or_else(overflow)?;
         return Ok(supplied_quantity);
     }
 
     pub fn burn_rev(
         &mut self,
-        asset: &mut MpAsset,
-        utilisable_quantity: Num,
-    ) -> Result<Num, MpError> {
+        asset: &mut MpAssetOf<N>,
+        utilisable_quantity: N,
+    ) -> Result<N, MpErrorOf<N>> {
+        self.atomically(asset, |context, asset| {
+            context.burn_rev_in_place(asset, utilisable_quantity)
+        })
+    }
+
+    fn burn_rev_in_place(
+        &mut self,
+        asset: &mut MpAssetOf<N>,
+        utilisable_quantity: N,
+    ) -> Result<N, MpErrorOf<N>> {
         let context = self;
+        let operation = MpOperation::BurnRev;
+        context.check_operands(asset, operation)?;
+        let overflow = || MpErrorOf::Overflow { operation };
+        let available = asset.quantity.clone();
+        let insufficient = |requested| MpErrorOf::InsufficientBurnQuantity {
+            operation,
+            requested,
+            available: available.clone(),
+        };
         if utilisable_quantity > asset.quantity {
-            return Err(MpError::InsufficientBurnQuantity);
+            return Err(insufficient(utilisable_quantity));
         }
 
-        let with_fees = get_suppliable_burn_quantity(
-            utilisable_quantity.into(),
-            &asset.sign(),
-            &context.sign(),
-        )
-        .abs();
-        let no_fees = utilisable_quantity * (Num::from("1") + context.operation_base_fee);
+        let with_fees = get_suppliable_burn_solution(utilisable_quantity.clone(), asset, context)?
+            .map(|solution| solution.quantity)
+            .unwrap_or_else(N::zero);
+        let base_fee = utilisable_quantity
+            .checked_mul(&context.operation_base_fee)
+            .ok_or_else(overflow)?;
+        let no_fees = utilisable_quantity
+            .checked_add(&base_fee)
+            .ok_or_else(overflow)?;
 
         let supplied_quantity;
 
-        let deviation_with_fees = calculate_deviation_burn(with_fees, asset, context);
-        let deviation_no_fees = calculate_deviation_burn(no_fees, asset, context);
-        let deviation_old = calculate_deviation_burn(Num::ZERO, asset, context);
+        let deviation_with_fees = calculate_deviation_burn(with_fees.clone(), asset, context);
+        let deviation_no_fees = calculate_deviation_burn(no_fees.clone(), asset, context);
+        let deviation_old = calculate_deviation_burn(N::zero(), asset, context);
 
         if deviation_no_fees <= deviation_old {
             supplied_quantity = no_fees;
             if supplied_quantity > asset.quantity {
-                return Err(MpError::InsufficientBurnQuantity);
+                return Err(insufficient(supplied_quantity));
             }
-            let cashback = if !deviation_old.is_zero() {
-                asset.collected_cashbacks * (deviation_old - deviation_no_fees) / deviation_old
-            } else {
-                Num::ZERO
-            };
-            asset.collected_cashbacks -= cashback;
-            context.user_cashback_balance += cashback;
+            let cashback = context.cashback(asset, &deviation_old, &deviation_no_fees);
+            asset.collected_cashbacks = asset
+                .collected_cashbacks
+                .checked_sub(&cashback)
+                .ok_or_else(overflow)?;
+            context.user_cashback_balance = context
+                .user_cashback_balance
+                .checked_add(&cashback)
+                .ok_or_else(overflow)?;
         } else {
             supplied_quantity = with_fees;
             if supplied_quantity > asset.quantity {
-                return Err(MpError::InsufficientBurnQuantity);
+                return Err(insufficient(supplied_quantity));
             }
             if deviation_with_fees > context.deviation_percent_limit {
-                return Err(MpError::DeviationBiggerThanLimit);
+                return Err(MpErrorOf::DeviationBiggerThanLimit {
+                    operation,
+                    deviation: deviation_with_fees,
+                    limit: context.deviation_percent_limit.clone(),
+                });
             }
-            if with_fees.is_zero() {
-                return Err(MpError::NoCurveSolutions);
+            if supplied_quantity.is_zero() {
+                return Err(MpErrorOf::NoCurveSolutions {
+                    operation,
+                    requested: utilisable_quantity,
+                });
             }
 
-            asset.collected_cashbacks += supplied_quantity
-                - utilisable_quantity
-                - utilisable_quantity * context.operation_base_fee;
+            let collected_deviation_fee = supplied_quantity
+                .checked_sub(&no_fees)
+                .ok_or_else(overflow)?;
+            asset.collected_cashbacks = asset
+                .collected_cashbacks
+                .checked_add(&collected_deviation_fee)
+                .ok_or_else(overflow)?;
         }
-        asset.quantity -= supplied_quantity;
-        context.total_current_usd_amount -= supplied_quantity * asset.price;
-        asset.collected_fees += utilisable_quantity * context.operation_base_fee;
+        asset.quantity = asset
+            .quantity
+            .checked_sub(&supplied_quantity)
+            .ok_or_else(overflow)?;
+        context.total_current_usd_amount = context
+            .total_current_usd_amount
+            .checked_sub(
+                &supplied_quantity
+                    .checked_mul(&asset.price)
+                    .ok_or_else(overflow)?,
+            )
+            .ok_or_else(overflow)?;
+        asset.collected_fees = asset
+            .collected_fees
+            .checked_add(&base_fee)
+            .ok_or_else(overflow)?;
         return Ok(supplied_quantity);
     }
 
-    pub fn mint(&mut self, asset: &mut MpAsset, supplied_quantity: Num) -> Result<Num, MpError> {
+    pub fn mint(
+        &mut self,
+        asset: &mut MpAssetOf<N>,
+        supplied_quantity: N,
+    ) -> Result<N, MpErrorOf<N>> {
+        self.atomically(asset, |context, asset| {
+            context.mint_in_place(asset, supplied_quantity)
+        })
+    }
+
+    fn mint_in_place(
+        &mut self,
+        asset: &mut MpAssetOf<N>,
+        supplied_quantity: N,
+    ) -> Result<N, MpErrorOf<N>> {
         let context = self;
+        let operation = MpOperation::Mint;
+        context.check_operands(asset, operation)?;
+        let overflow = || MpErrorOf::Overflow { operation };
         if context.total_current_usd_amount.is_zero() {
-            context.total_current_usd_amount = supplied_quantity * asset.price;
-            asset.quantity += supplied_quantity;
+            let usd_amount = supplied_quantity
+                .checked_mul(&asset.price)
+                .ok_or_else(overflow)?;
+            asset.quantity = asset
+                .quantity
+                .checked_add(&supplied_quantity)
+                .ok_or_else(overflow)?;
+            context.total_current_usd_amount = usd_amount;
             return Ok(supplied_quantity);
         }
         let utilisable_quantity;
 
-        let with_fees =
-            get_utilisable_mint_quantity(supplied_quantity.into(), &asset.sign(), &context.sign())
-                .abs();
-        let no_fees = supplied_quantity / (Num::from("1") + context.operation_base_fee);
+        let with_fees = get_utilisable_mint_solution(supplied_quantity.clone(), asset, context)?
+            .map(|solution| solution.quantity)
+            .unwrap_or_else(N::zero);
+        let no_fees = N::one()
+            .checked_add(&context.operation_base_fee)
+            .and_then(|bf| supplied_quantity.checked_div(&bf))
+            .ok_or_else(overflow)?;
 
-        let deviation_with_fees = calculate_deviation_mint(with_fees, asset, context);
-        let deviation_no_fees = calculate_deviation_mint(no_fees, asset, context);
-        let deviation_old = calculate_deviation_mint(Num::ZERO, asset, context);
+        let deviation_with_fees = calculate_deviation_mint(with_fees.clone(), asset, context);
+        let deviation_no_fees = calculate_deviation_mint(no_fees.clone(), asset, context);
+        let deviation_old = calculate_deviation_mint(N::zero(), asset, context);
 
         if deviation_no_fees <= deviation_old {
             utilisable_quantity = no_fees;
-            let cashback = if !deviation_old.is_zero() {
-                asset.collected_cashbacks * (deviation_old - deviation_no_fees) / deviation_old
-            } else {
-                Num::ZERO
-            };
-            asset.collected_cashbacks -= cashback;
-            context.user_cashback_balance += cashback;
+            let cashback = context.cashback(asset, &deviation_old, &deviation_no_fees);
+            asset.collected_cashbacks = asset
+                .collected_cashbacks
+                .checked_sub(&cashback)
+                .ok_or_else(overflow)?;
+            context.user_cashback_balance = context
+                .user_cashback_balance
+                .checked_add(&cashback)
+                .ok_or_else(overflow)?;
         } else {
             utilisable_quantity = with_fees;
             if deviation_with_fees > context.deviation_percent_limit {
-                return Err(MpError::DeviationBiggerThanLimit);
+                return Err(MpErrorOf::DeviationBiggerThanLimit {
+                    operation,
+                    deviation: deviation_with_fees,
+                    limit: context.deviation_percent_limit.clone(),
+                });
             }
-            if with_fees.is_zero() {
-                return Err(MpError::NoCurveSolutions);
+            if utilisable_quantity.is_zero() {
+                return Err(MpErrorOf::NoCurveSolutions {
+                    operation,
+                    requested: supplied_quantity,
+                });
             }
 
-            asset.collected_cashbacks += supplied_quantity
-                - utilisable_quantity
-                - utilisable_quantity * context.operation_base_fee;
+            let collected_deviation_fee = utilisable_quantity
+                .checked_mul(&context.operation_base_fee)
+                .and_then(|base_fee| base_fee.checked_add(&utilisable_quantity))
+                .and_then(|charged| supplied_quantity.checked_sub(&charged))
+                .ok_or_else(overflow)?;
+            asset.collected_cashbacks = asset
+                .collected_cashbacks
+                .checked_add(&collected_deviation_fee)
+                .ok_or_else(overflow)?;
         }
-        asset.quantity += utilisable_quantity;
-        context.total_current_usd_amount += utilisable_quantity * asset.price;
-        asset.collected_fees += utilisable_quantity * context.operation_base_fee;
+        let usd_amount = utilisable_quantity
+            .checked_mul(&asset.price)
+            .ok_or_else(overflow)?;
+        asset.quantity = asset
+            .quantity
+            .checked_add(&utilisable_quantity)
+            .ok_or_else(overflow)?;
+        context.total_current_usd_amount = context
+            .total_current_usd_amount
+            .checked_add(&usd_amount)
+            .ok_or_else(overflow)?;
+        asset.collected_fees = asset
+            .collected_fees
+            .checked_add(
+                &utilisable_quantity
+                    .checked_mul(&context.operation_base_fee)
+                    .ok_or_else(overflow)?,
+            )
+            .ok_or_else(overflow)?;
         return Ok(utilisable_quantity);
     }
 
-    pub fn burn(&mut self, asset: &mut MpAsset, supplied_quantity: Num) -> Result<Num, MpError> {
+    pub fn burn(
+        &mut self,
+        asset: &mut MpAssetOf<N>,
+        supplied_quantity: N,
+    ) -> Result<N, MpErrorOf<N>> {
+        self.atomically(asset, |context, asset| {
+            context.burn_in_place(asset, supplied_quantity)
+        })
+    }
+
+    fn burn_in_place(
+        &mut self,
+        asset: &mut MpAssetOf<N>,
+        supplied_quantity: N,
+    ) -> Result<N, MpErrorOf<N>> {
         let context = self;
+        let operation = MpOperation::Burn;
+        context.check_operands(asset, operation)?;
+        let overflow = || MpErrorOf::Overflow { operation };
         if supplied_quantity > asset.quantity {
-            return Err(MpError::InsufficientBurnQuantity);
+            return Err(MpErrorOf::InsufficientBurnQuantity {
+                operation,
+                requested: supplied_quantity,
+                available: asset.quantity.clone(),
+            });
         }
 
         let utilisable_quantity;
 
-        let deviation_new = calculate_deviation_burn(supplied_quantity, asset, context);
-        let deviation_old = calculate_deviation_burn(Num::ZERO, asset, context);
+        let deviation_new = calculate_deviation_burn(supplied_quantity.clone(), asset, context);
+        let deviation_old = calculate_deviation_burn(N::zero(), asset, context);
 
         if deviation_new <= deviation_old {
-            let cashback = if !deviation_old.is_zero() {
-                asset.collected_cashbacks * (deviation_old - deviation_new) / deviation_old
-            } else {
-                Num::ZERO
-            };
-            asset.collected_cashbacks -= cashback;
-            context.user_cashback_balance += cashback;
-            utilisable_quantity = supplied_quantity / (Num::from("1") + context.operation_base_fee);
+            let cashback = context.cashback(asset, &deviation_old, &deviation_new);
+            asset.collected_cashbacks = asset
+                .collected_cashbacks
+                .checked_sub(&cashback)
+                .ok_or_else(overflow)?;
+            context.user_cashback_balance = context
+                .user_cashback_balance
+                .checked_add(&cashback)
+                .ok_or_else(overflow)?;
+            utilisable_quantity = N::one()
+                .checked_add(&context.operation_base_fee)
+                .and_then(|bf| supplied_quantity.checked_div(&bf))
+                .ok_or_else(overflow)?;
         } else {
             if deviation_new > context.deviation_percent_limit {
-                return Err(MpError::DeviationBiggerThanLimit);
+                return Err(MpErrorOf::DeviationBiggerThanLimit {
+                    operation,
+                    deviation: deviation_new,
+                    limit: context.deviation_percent_limit.clone(),
+                });
             }
 
-            let fee_ratio = context.curve_coef * deviation_new
-                / context.deviation_percent_limit
-                / (context.deviation_percent_limit - deviation_new);
-            utilisable_quantity =
-                supplied_quantity / (Num::from("1") + fee_ratio + context.operation_base_fee);
-
-            asset.collected_cashbacks += supplied_quantity
-                - utilisable_quantity
-                - utilisable_quantity * context.operation_base_fee;
+            utilisable_quantity = context
+                .deviation_fee(&deviation_new, &N::one())
+                .and_then(|ratio| ratio.checked_add(&N::one()))
+                .and_then(|ratio| ratio.checked_add(&context.operation_base_fee))
+                .and_then(|ratio| supplied_quantity.checked_div(&ratio))
+                .ok_or_else(overflow)?;
+
+            let collected_deviation_fee = utilisable_quantity
+                .checked_mul(&context.operation_base_fee)
+                .and_then(|base_fee| base_fee.checked_add(&utilisable_quantity))
+                .and_then(|charged| supplied_quantity.checked_sub(&charged))
+                .ok_or_else(overflow)?;
+            asset.collected_cashbacks = asset
+                .collected_cashbacks
+                .checked_add(&collected_deviation_fee)
+                .ok_or_else(overflow)?;
         }
-        asset.quantity -= supplied_quantity;
-        context.total_current_usd_amount -= supplied_quantity * asset.price;
-        asset.collected_fees += utilisable_quantity * context.operation_base_fee;
+        asset.quantity = asset
+            .quantity
+            .checked_sub(&supplied_quantity)
+            .ok_or_else(overflow)?;
+        context.total_current_usd_amount = context
+            .total_current_usd_amount
+            .checked_sub(
+                &supplied_quantity
+                    .checked_mul(&asset.price)
+                    .ok_or_else(overflow)?,
+            )
+            .ok_or_else(overflow)?;
+        asset.collected_fees = asset
+            .collected_fees
+            .checked_add(
+                &utilisable_quantity
+                    .checked_mul(&context.operation_base_fee)
+                    .ok_or_else(overflow)?,
+            )
+            .ok_or_else(overflow)?;
         return Ok(utilisable_quantity);
     }
 }
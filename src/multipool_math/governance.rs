@@ -0,0 +1,161 @@
+use serde::Serialize;
+
+use super::{calculate_deviation_mint, pool::MpPool, MpError, MpOperation};
+use crate::num::num::Num;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ParameterChange {
+    CurveCoef(Num),
+    DeviationPercentLimit(Num),
+    OperationBaseFee(Num),
+    AssetPercent { address: String, percent: Num },
+}
+
+/// Operation that earns cashback for an asset: minting an asset under its
+/// target share or burning one over it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+pub enum CashbackIncentive {
+    Mint,
+    Burn,
+    None,
+}
+
+impl CashbackIncentive {
+    pub(super) fn for_share(share: Num, target: Num) -> Self {
+        if share < target {
+            CashbackIncentive::Mint
+        } else if share > target {
+            CashbackIncentive::Burn
+        } else {
+            CashbackIncentive::None
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AssetChange {
+    pub address: String,
+    pub deviation_before: Num,
+    pub deviation_after: Num,
+    pub out_of_bounds_before: bool,
+    pub out_of_bounds_after: bool,
+    pub incentive_before: CashbackIncentive,
+    pub incentive_after: CashbackIncentive,
+}
+
+/// Mint of `amount` of the asset or burn of `amount` shares into it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ReferenceTrade {
+    pub address: String,
+    pub operation: MpOperation,
+    pub amount: Num,
+}
+
+/// Usd fee of a reference trade, `None` if the trade fails.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TradeChange {
+    pub trade: ReferenceTrade,
+    pub fee_before: Option<Num>,
+    pub fee_after: Option<Num>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ChangeReport {
+    /// Pool with the changes applied.
+    pub pool: MpPool,
+    pub assets: Vec<AssetChange>,
+    pub trades: Vec<TradeChange>,
+}
+
+impl MpPool {
+    pub fn apply_change(&mut self, change: &ParameterChange) -> Result<(), MpError> {
+        match change {
+            ParameterChange::CurveCoef(value) => self.context.curve_coef = *value,
+            ParameterChange::DeviationPercentLimit(value) => {
+                self.context.deviation_percent_limit = *value
+            }
+            ParameterChange::OperationBaseFee(value) => self.context.operation_base_fee = *value,
+            ParameterChange::AssetPercent { address, percent } => {
+                let operation = MpOperation::Reconfigure;
+                let old = self.asset(address, operation)?.percent;
+                self.context.total_asset_percents = (self.context.total_asset_percents - old)
+                    .checked_add(*percent)
+                    .ok_or(MpError::Overflow { operation })?;
+                if let Some(asset) = self.assets.get_mut(address) {
+                    asset.percent = *percent;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies `changes` to a copy of the pool and compares asset bounds,
+    /// cashback incentives and fees of `trades` before and after.
+    pub fn simulate_changes(
+        &self,
+        changes: &[ParameterChange],
+        trades: &[ReferenceTrade],
+    ) -> Result<ChangeReport, MpError> {
+        let mut pool = self.clone();
+        for change in changes {
+            pool.apply_change(change)?;
+        }
+        let assets = self
+            .assets
+            .keys()
+            .map(|address| {
+                let (deviation_before, out_of_bounds_before, incentive_before) =
+                    self.asset_state(address);
+                let (deviation_after, out_of_bounds_after, incentive_after) =
+                    pool.asset_state(address);
+                AssetChange {
+                    address: address.clone(),
+                    deviation_before,
+                    deviation_after,
+                    out_of_bounds_before,
+                    out_of_bounds_after,
+                    incentive_before,
+                    incentive_after,
+                }
+            })
+            .collect();
+        let trades = trades
+            .iter()
+            .map(|trade| TradeChange {
+                trade: trade.clone(),
+                fee_before: self.trade_fee(trade),
+                fee_after: pool.trade_fee(trade),
+            })
+            .collect();
+        Ok(ChangeReport {
+            pool,
+            assets,
+            trades,
+        })
+    }
+
+    fn asset_state(&self, address: &str) -> (Num, bool, CashbackIncentive) {
+        let asset = &self.assets[address];
+        let deviation = calculate_deviation_mint(Num::ZERO, asset, &self.context);
+        let share = asset.quantity * asset.price / self.context.total_current_usd_amount;
+        let target = asset.percent / self.context.total_asset_percents;
+        let incentive = CashbackIncentive::for_share(share, target);
+        (
+            deviation,
+            deviation > self.context.deviation_percent_limit,
+            incentive,
+        )
+    }
+
+    fn trade_fee(&self, trade: &ReferenceTrade) -> Option<Num> {
+        let mut pool = self.clone();
+        match trade.operation {
+            MpOperation::Mint => pool.mint(&trade.address, trade.amount).ok()?,
+            MpOperation::MintRev => pool.mint_rev(&trade.address, trade.amount).ok()?,
+            MpOperation::Burn => pool.burn(&trade.address, trade.amount).ok()?,
+            MpOperation::BurnRev => pool.burn_rev(&trade.address, trade.amount).ok()?,
+            MpOperation::Reconfigure | MpOperation::Payout | MpOperation::Valuation => return None,
+        };
+        Some(pool.fees_since(self).0)
+    }
+}
@@ -0,0 +1,109 @@
+use std::fmt::{Display, Formatter};
+
+use serde::Serialize;
+
+use super::{governance::CashbackIncentive, pool::MpPool};
+use crate::num::{num::Num, snum::SNum};
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct AssetHealth {
+    pub address: String,
+    pub current_share: Num,
+    pub target_share: Num,
+    pub deviation: Num,
+    /// Deviation left before the limit, zero for assets beyond it.
+    pub headroom: Num,
+    /// Collected cashback quantity paid to trades moving the asset towards
+    /// its target.
+    pub pending_cashback: Num,
+    pub incentive: CashbackIncentive,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct PoolHealth {
+    pub assets: Vec<AssetHealth>,
+    /// Target weighted headroom relative to the limit: one for a pool at
+    /// its target weights, zero once every asset reaches the limit.
+    pub score: Num,
+}
+
+impl MpPool {
+    pub fn health(&self) -> PoolHealth {
+        let context = &self.context;
+        let limit = context.deviation_percent_limit;
+        let assets: Vec<AssetHealth> = self
+            .assets
+            .iter()
+            .map(|(address, asset)| {
+                let current_share = (asset.quantity * asset.price)
+                    .checked_div(context.total_current_usd_amount)
+                    .unwrap_or(Num::ZERO);
+                let target_share = asset
+                    .percent
+                    .checked_div(context.total_asset_percents)
+                    .unwrap_or(Num::ZERO);
+                let deviation = (SNum::from(current_share) - target_share.into()).abs();
+                AssetHealth {
+                    address: address.clone(),
+                    current_share,
+                    target_share,
+                    deviation,
+                    headroom: limit.checked_sub(deviation).unwrap_or(Num::ZERO),
+                    pending_cashback: asset.collected_cashbacks,
+                    incentive: CashbackIncentive::for_share(current_share, target_share),
+                }
+            })
+            .collect();
+        let score = assets
+            .iter()
+            .map(|asset| asset.target_share * asset.headroom)
+            .sum::<Num>()
+            .checked_div(limit)
+            .unwrap_or(Num::ZERO);
+        PoolHealth { assets, score }
+    }
+}
+
+impl PoolHealth {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+}
+
+impl Display for PoolHealth {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let header = [
+            "asset",
+            "share",
+            "target",
+            "deviation",
+            "headroom",
+            "cashback",
+            "incentive",
+        ];
+        let mut rows = vec![header.map(String::from).to_vec()];
+        rows.extend(self.assets.iter().map(|asset| {
+            vec![
+                asset.address.clone(),
+                asset.current_share.to_string(),
+                asset.target_share.to_string(),
+                asset.deviation.to_string(),
+                asset.headroom.to_string(),
+                asset.pending_cashback.to_string(),
+                format!("{:?}", asset.incentive),
+            ]
+        }));
+        let widths: Vec<usize> = (0..header.len())
+            .map(|i| rows.iter().map(|row| row[i].len()).max().unwrap_or(0))
+            .collect();
+        for row in &rows {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect();
+            writeln!(f, "{}", cells.join("  ").trim_end())?;
+        }
+        write!(f, "score: {}", self.score)
+    }
+}
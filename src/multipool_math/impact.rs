@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+use super::{pool::MpPool, valuation::ValueUnit, MpError, MpOperation};
+use crate::num::{num::Num, snum::SNum};
+
+/// Execution price of a trade against the pool. Mints and burns are priced
+/// in usd per share, swaps in units of asset out per unit of asset in.
+/// Positive impact means the trade executes worse than the mid price.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct PriceImpact {
+    pub mid_price: Num,
+    pub effective_price: Num,
+    pub impact_bps: SNum,
+}
+
+impl PriceImpact {
+    fn new(mid_price: Num, effective_price: Num, paying: bool) -> Self {
+        let difference = if paying {
+            SNum::from(effective_price) - mid_price.into()
+        } else {
+            SNum::from(mid_price) - effective_price.into()
+        };
+        let impact_bps = if mid_price.is_zero() {
+            SNum::ZERO
+        } else {
+            difference * Num::from("10000").into() / mid_price.into()
+        };
+        Self {
+            mid_price,
+            effective_price,
+            impact_bps,
+        }
+    }
+
+    fn share_price(before: &MpPool) -> Num {
+        before.share_price(&ValueUnit::Usd).unwrap_or(Num::ZERO)
+    }
+
+    /// Price of `asset` in `before`, failing for unknown assets.
+    fn price(before: &MpPool, asset: &str, operation: MpOperation) -> Result<Num, MpError> {
+        Ok(before.asset(asset, operation)?.price)
+    }
+
+    /// Fails for a zero divisor of the effective price, which would
+    /// otherwise read as a zero price.
+    fn nonzero(amount: Num, operation: MpOperation) -> Result<Num, MpError> {
+        if amount.is_zero() {
+            return Err(MpError::ZeroAmount { operation });
+        }
+        Ok(amount)
+    }
+
+    /// Impact of supplying `amount_in` of `asset` for `shares`, `before`
+    /// being the pool state prior to the trade.
+    pub fn mint(
+        before: &MpPool,
+        asset: &str,
+        amount_in: Num,
+        shares: Num,
+    ) -> Result<Self, MpError> {
+        let operation = MpOperation::Mint;
+        let price = Self::price(before, asset, operation)?;
+        let shares = Self::nonzero(shares, operation)?;
+        Ok(Self::new(
+            Self::share_price(before),
+            amount_in * price / shares,
+            true,
+        ))
+    }
+
+    /// Impact of burning `shares` for `amount_out` of `asset`.
+    pub fn burn(
+        before: &MpPool,
+        asset: &str,
+        shares: Num,
+        amount_out: Num,
+    ) -> Result<Self, MpError> {
+        let operation = MpOperation::Burn;
+        let price = Self::price(before, asset, operation)?;
+        let shares = Self::nonzero(shares, operation)?;
+        Ok(Self::new(
+            Self::share_price(before),
+            amount_out * price / shares,
+            false,
+        ))
+    }
+
+    /// Impact of swapping `amount_in` of `asset_in` for `amount_out` of
+    /// `asset_out`.
+    pub fn swap(
+        before: &MpPool,
+        asset_in: &str,
+        asset_out: &str,
+        amount_in: Num,
+        amount_out: Num,
+    ) -> Result<Self, MpError> {
+        let price_in = Self::price(before, asset_in, MpOperation::Mint)?;
+        let price_out = Self::price(before, asset_out, MpOperation::Burn)?;
+        if price_out.is_zero() {
+            return Err(MpError::ZeroPrice {
+                operation: MpOperation::Burn,
+            });
+        }
+        let amount_in = Self::nonzero(amount_in, MpOperation::Mint)?;
+        Ok(Self::new(
+            price_in / price_out,
+            amount_out / amount_in,
+            false,
+        ))
+    }
+}
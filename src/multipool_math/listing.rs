@@ -0,0 +1,87 @@
+use super::{
+    governance::ParameterChange, pool::MpPool, rebalance::RebalancePlan, MpAsset, MpError,
+    MpOperation,
+};
+use crate::num::num::Num;
+
+impl MpPool {
+    /// Lists a new asset. Its target percent is added to
+    /// `total_asset_percents` and its current value to the pool value.
+    pub fn add_asset(&mut self, address: &str, asset: MpAsset) -> Result<(), MpError> {
+        let operation = MpOperation::Reconfigure;
+        if self.assets.contains_key(address) {
+            return Err(MpError::AssetAlreadyListed {
+                operation,
+                address: address.to_owned(),
+            });
+        }
+        let overflow = || MpError::Overflow { operation };
+        let total_asset_percents = self
+            .context
+            .total_asset_percents
+            .checked_add(asset.percent)
+            .ok_or_else(overflow)?;
+        let total_current_usd_amount = asset
+            .quantity
+            .checked_mul(asset.price)
+            .and_then(|value| self.context.total_current_usd_amount.checked_add(value))
+            .ok_or_else(overflow)?;
+        self.context.total_asset_percents = total_asset_percents;
+        self.context.total_current_usd_amount = total_current_usd_amount;
+        self.assets.insert(address.to_owned(), asset);
+        Ok(())
+    }
+
+    /// Sunsets an asset by setting its target percent to zero. The asset
+    /// can't be minted anymore, but stays in the pool until it is burned
+    /// out. An asset without any quantity left is dropped right away. The
+    /// last weighted asset can't be removed, as no asset could be burned
+    /// without target percents.
+    pub fn remove_asset(&mut self, address: &str) -> Result<(), MpError> {
+        let operation = MpOperation::Reconfigure;
+        let asset = self.asset(address, operation)?;
+        let quantity = asset.quantity;
+        if !asset.percent.is_zero() && asset.percent >= self.context.total_asset_percents {
+            return Err(MpError::EmptyBasket { operation });
+        }
+        self.apply_change(&ParameterChange::AssetPercent {
+            address: address.to_owned(),
+            percent: Num::ZERO,
+        })?;
+        if quantity.is_zero() {
+            self.assets.remove(address);
+        }
+        Ok(())
+    }
+
+    /// Estimates trades worth at most `budget` usd that move the pool to new
+    /// target percents. Assets missing from the pool are listed with zero
+    /// quantity at the given price.
+    pub fn plan_migration(
+        &self,
+        targets: &[(&str, Num)],
+        prices: &[(&str, Num)],
+        budget: Num,
+    ) -> Result<RebalancePlan, MpError> {
+        let mut pool = self.clone();
+        for (address, price) in prices {
+            if !pool.assets.contains_key(*address) {
+                let asset = MpAsset {
+                    quantity: Num::ZERO,
+                    price: *price,
+                    collected_fees: Num::ZERO,
+                    collected_cashbacks: Num::ZERO,
+                    percent: Num::ZERO,
+                };
+                pool.add_asset(address, asset)?;
+            }
+        }
+        for (address, percent) in targets {
+            pool.apply_change(&ParameterChange::AssetPercent {
+                address: address.to_string(),
+                percent: *percent,
+            })?;
+        }
+        Ok(pool.plan_rebalance(budget))
+    }
+}
@@ -1,3 +1,15 @@
 pub mod actions;
+pub mod basket;
 pub mod core_math;
+pub mod governance;
+pub mod health;
+pub mod impact;
+pub mod listing;
+pub mod payout;
+pub mod pool;
+pub mod rebalance;
+pub mod sampling;
+pub mod selector;
+pub mod solver;
+pub mod valuation;
 pub use core_math::*;
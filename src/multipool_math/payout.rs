@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+
+use super::{pool::MpPool, MpError, MpOperation};
+use crate::num::num::Num;
+
+/// Asset quantities paid out of the pool by a cashback claim or a fee
+/// withdrawal.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Payout {
+    pub quantities: BTreeMap<String, Num>,
+    /// Value of the quantities at current asset prices.
+    pub usd_value: Num,
+}
+
+impl Payout {
+    fn add(&mut self, address: &str, quantity: Num, price: Num) {
+        *self
+            .quantities
+            .entry(address.to_owned())
+            .or_insert(Num::ZERO) += quantity;
+        self.usd_value += quantity * price;
+    }
+}
+
+impl MpPool {
+    /// Runs `trade` on behalf of `user` and credits them with the cashback
+    /// it earned. The pool is left untouched if the trade fails.
+    pub fn trade_as<T, F>(&mut self, user: &str, trade: F) -> Result<T, MpError>
+    where
+        F: FnOnce(&mut MpPool) -> Result<T, MpError>,
+    {
+        let mut pool = self.clone();
+        let result = trade(&mut pool)?;
+        for (address, old) in &self.assets {
+            let Some(new) = pool.assets.get(address) else {
+                continue;
+            };
+            if new.collected_cashbacks < old.collected_cashbacks {
+                *pool
+                    .cashbacks
+                    .entry(user.to_owned())
+                    .or_default()
+                    .entry(address.clone())
+                    .or_insert(Num::ZERO) += old.collected_cashbacks - new.collected_cashbacks;
+            }
+        }
+        *self = pool;
+        Ok(result)
+    }
+
+    /// Unclaimed cashback of `user` with its current usd value.
+    pub fn cashback_of(&self, user: &str) -> Payout {
+        let mut payout = Payout::default();
+        for (address, quantity) in self.cashbacks.get(user).into_iter().flatten() {
+            let price = self
+                .assets
+                .get(address)
+                .map_or(Num::ZERO, |asset| asset.price);
+            payout.add(address, *quantity, price);
+        }
+        payout
+    }
+
+    /// Pays out the cashback attributed to `user` and returns the claimed
+    /// quantity of every asset. Each quantity is taken off
+    /// `user_cashback_balance`, which core math credits per asset the same
+    /// way. Cashback is already excluded from asset quantities, so the pool
+    /// value and the share price don't change.
+    pub fn claim_cashback(&mut self, user: &str) -> Result<BTreeMap<String, Num>, MpError> {
+        let operation = MpOperation::Payout;
+        let claimed = self.cashbacks.get(user).cloned().unwrap_or_default();
+        let mut balance = self.context.user_cashback_balance;
+        for quantity in claimed.values() {
+            balance = balance
+                .checked_sub(*quantity)
+                .ok_or(MpError::Overflow { operation })?;
+        }
+        self.context.user_cashback_balance = balance;
+        self.cashbacks.remove(user);
+        Ok(claimed)
+    }
+
+    /// Withdraws `collected_fees` of every asset to the protocol treasury.
+    /// Like cashback, fees are held apart from asset quantities, so the
+    /// pool value and the share price don't change.
+    pub fn withdraw_fees(&mut self) -> Payout {
+        let mut payout = Payout::default();
+        for (address, asset) in self.assets.iter_mut() {
+            if asset.collected_fees.is_zero() {
+                continue;
+            }
+            payout.add(address, asset.collected_fees, asset.price);
+            asset.collected_fees = Num::ZERO;
+        }
+        payout
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+
+use super::{MpAsset, MpContext, MpError, MpOperation};
+use crate::num::{num::Num, snum::SNum};
+
+/// Rounding direction of share conversions. Like the contract, conversions
+/// are always rounded in favour of the pool.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+/// Converts a usd value to shares at the pool share price. The first
+/// deposit into an empty pool issues one share per usd.
+pub fn shares_for_value(
+    value: Num,
+    total_value: Num,
+    total_supply: Num,
+    rounding: Rounding,
+) -> Option<Num> {
+    if total_supply.is_zero() || total_value.is_zero() {
+        return Some(value);
+    }
+    match rounding {
+        Rounding::Down => value.mul_div(total_supply, total_value),
+        Rounding::Up => value.mul_div_up(total_supply, total_value),
+    }
+}
+
+/// Converts shares to their usd value at the pool share price.
+pub fn value_for_shares(
+    shares: Num,
+    total_value: Num,
+    total_supply: Num,
+    rounding: Rounding,
+) -> Option<Num> {
+    if total_supply.is_zero() || total_value.is_zero() {
+        return Some(shares);
+    }
+    match rounding {
+        Rounding::Down => shares.mul_div(total_value, total_supply),
+        Rounding::Up => shares.mul_div_up(total_value, total_supply),
+    }
+}
+
+/// Converts a usd value to a quantity of the asset.
+pub(super) fn quantity_for_value(
+    value: Num,
+    asset: &MpAsset,
+    rounding: Rounding,
+    operation: MpOperation,
+) -> Result<Num, MpError> {
+    if asset.price.is_zero() {
+        return Err(MpError::ZeroPrice { operation });
+    }
+    let one = Num::from("1");
+    match rounding {
+        Rounding::Down => value.mul_div(one, asset.price),
+        Rounding::Up => value.mul_div_up(one, asset.price),
+    }
+    .ok_or(MpError::Overflow { operation })
+}
+
+/// Whole pool state: the shared context, every asset keyed by its address
+/// and the issued share supply.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MpPool {
+    pub context: MpContext,
+    pub assets: BTreeMap<String, MpAsset>,
+    pub total_supply: Num,
+    /// Unclaimed cashback quantities keyed by user and then by asset.
+    pub cashbacks: BTreeMap<String, BTreeMap<String, Num>>,
+}
+
+impl MpPool {
+    pub fn new(context: MpContext, total_supply: Num) -> Self {
+        Self {
+            context,
+            assets: BTreeMap::new(),
+            total_supply,
+            cashbacks: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_asset(mut self, address: &str, asset: MpAsset) -> Self {
+        self.assets.insert(address.to_owned(), asset);
+        self
+    }
+
+    pub fn asset(&self, address: &str, operation: MpOperation) -> Result<&MpAsset, MpError> {
+        self.assets
+            .get(address)
+            .ok_or_else(|| MpError::UnknownAsset {
+                operation,
+                address: address.to_owned(),
+            })
+    }
+
+    /// Mints with `supplied_quantity` of the asset and returns issued shares.
+    pub fn mint(&mut self, address: &str, supplied_quantity: Num) -> Result<Num, MpError> {
+        let operation = MpOperation::Mint;
+        let mut asset = self.asset(address, operation)?.clone();
+        let mut context = self.context.clone();
+        let utilisable_quantity = context.mint(&mut asset, supplied_quantity)?;
+        let shares = utilisable_quantity
+            .checked_mul(asset.price)
+            .and_then(|value| {
+                shares_for_value(
+                    value,
+                    self.context.total_current_usd_amount,
+                    self.total_supply,
+                    Rounding::Down,
+                )
+            })
+            .ok_or(MpError::Overflow { operation })?;
+        self.issue(address, asset, context, shares, operation)?;
+        Ok(shares)
+    }
+
+    /// Mints exactly `shares` and returns the asset quantity to supply.
+    pub fn mint_rev(&mut self, address: &str, shares: Num) -> Result<Num, MpError> {
+        let operation = MpOperation::MintRev;
+        let mut asset = self.asset(address, operation)?.clone();
+        let mut context = self.context.clone();
+        let overflow = || MpError::Overflow { operation };
+        let value = value_for_shares(
+            shares,
+            self.context.total_current_usd_amount,
+            self.total_supply,
+            Rounding::Up,
+        )
+        .ok_or_else(overflow)?;
+        let utilisable_quantity = quantity_for_value(value, &asset, Rounding::Up, operation)?;
+        let supplied_quantity = context.mint_rev(&mut asset, utilisable_quantity)?;
+        self.issue(address, asset, context, shares, operation)?;
+        Ok(supplied_quantity)
+    }
+
+    /// Burns `shares` into the asset and returns the quantity paid out.
+    pub fn burn(&mut self, address: &str, shares: Num) -> Result<Num, MpError> {
+        let operation = MpOperation::Burn;
+        self.check_shares(shares, operation)?;
+        let mut asset = self.asset(address, operation)?.clone();
+        let mut context = self.context.clone();
+        let overflow = || MpError::Overflow { operation };
+        let value = value_for_shares(
+            shares,
+            self.context.total_current_usd_amount,
+            self.total_supply,
+            Rounding::Down,
+        )
+        .ok_or_else(overflow)?;
+        let supplied_quantity = quantity_for_value(value, &asset, Rounding::Down, operation)?;
+        let utilisable_quantity = context.burn(&mut asset, supplied_quantity)?;
+        self.redeem(address, asset, context, shares);
+        Ok(utilisable_quantity)
+    }
+
+    /// Burns as many shares as needed to pay out `utilisable_quantity` of the
+    /// asset and returns the burned shares.
+    pub fn burn_rev(&mut self, address: &str, utilisable_quantity: Num) -> Result<Num, MpError> {
+        let operation = MpOperation::BurnRev;
+        let mut asset = self.asset(address, operation)?.clone();
+        let mut context = self.context.clone();
+        let supplied_quantity = context.burn_rev(&mut asset, utilisable_quantity)?;
+        let shares = supplied_quantity
+            .checked_mul(asset.price)
+            .and_then(|value| {
+                shares_for_value(
+                    value,
+                    self.context.total_current_usd_amount,
+                    self.total_supply,
+                    Rounding::Up,
+                )
+            })
+            .ok_or(MpError::Overflow { operation })?;
+        self.check_shares(shares, operation)?;
+        self.redeem(address, asset, context, shares);
+        Ok(shares)
+    }
+
+    /// Swaps by minting with `asset_in` and burning the minted shares into
+    /// `asset_out`. Returns the routed shares and the quantity paid out.
+    pub fn swap(
+        &mut self,
+        asset_in: &str,
+        asset_out: &str,
+        supplied_quantity: Num,
+    ) -> Result<(Num, Num), MpError> {
+        let mut pool = self.clone();
+        let shares = pool.mint(asset_in, supplied_quantity)?;
+        let utilisable_quantity = pool.burn(asset_out, shares)?;
+        *self = pool;
+        Ok((shares, utilisable_quantity))
+    }
+
+    /// Usd value of fees collected and cashback paid out since `before`.
+    /// Fees withdrawn in between are not counted.
+    pub fn fees_since(&self, before: &MpPool) -> (Num, Num) {
+        let (mut fee, mut cashback) = (Num::ZERO, Num::ZERO);
+        for (address, old) in &before.assets {
+            let Some(new) = self.assets.get(address) else {
+                continue;
+            };
+            // withdrawn fees aren't a negative fee of the trades since
+            if let Some(collected) = new.collected_fees.checked_sub(old.collected_fees) {
+                fee += collected * new.price;
+            }
+            if new.collected_cashbacks >= old.collected_cashbacks {
+                fee += (new.collected_cashbacks - old.collected_cashbacks) * new.price;
+            } else {
+                cashback += (old.collected_cashbacks - new.collected_cashbacks) * new.price;
+            }
+        }
+        (fee, cashback)
+    }
+
+    /// Moves asset quantities `fraction` of the way back to `target`, the
+    /// way arbitrageurs rebalance the pool between trades. Share supply,
+    /// fees and cashbacks are left as is.
+    pub fn revert_towards(&mut self, target: &MpPool, fraction: Num) {
+        let mut total = SNum::from(self.context.total_current_usd_amount);
+        for (address, asset) in self.assets.iter_mut() {
+            let Some(goal) = target.assets.get(address) else {
+                continue;
+            };
+            let delta = (SNum::from(goal.quantity) - asset.quantity.into()) * fraction.into();
+            asset.quantity = (SNum::from(asset.quantity) + delta).abs();
+            total += delta * asset.price.into();
+        }
+        self.context.total_current_usd_amount = total.abs();
+    }
+
+    fn check_shares(&self, shares: Num, operation: MpOperation) -> Result<(), MpError> {
+        if shares > self.total_supply {
+            return Err(MpError::InsufficientShares {
+                operation,
+                requested: shares,
+                available: self.total_supply,
+            });
+        }
+        Ok(())
+    }
+
+    fn issue(
+        &mut self,
+        address: &str,
+        asset: MpAsset,
+        context: MpContext,
+        shares: Num,
+        operation: MpOperation,
+    ) -> Result<(), MpError> {
+        self.total_supply = self
+            .total_supply
+            .checked_add(shares)
+            .ok_or(MpError::Overflow { operation })?;
+        self.context = context;
+        self.assets.insert(address.to_owned(), asset);
+        Ok(())
+    }
+
+    fn redeem(&mut self, address: &str, asset: MpAsset, context: MpContext, shares: Num) {
+        self.total_supply -= shares;
+        self.context = context;
+        self.assets.insert(address.to_owned(), asset);
+    }
+}
@@ -0,0 +1,190 @@
+use super::{
+    calculate_deviation_mint,
+    pool::{shares_for_value, MpPool, Rounding},
+    MpError,
+};
+use crate::num::num::Num;
+
+/// Number of chunks the budget is initially split into.
+const REBALANCE_CHUNKS: u32 = 8;
+
+/// Chunks are halved while no trade improves the pool, down to
+/// `budget / 2^MAX_HALVINGS`.
+const MAX_HALVINGS: u32 = 10;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum RebalanceAction {
+    Mint {
+        address: String,
+        quantity: Num,
+        shares: Num,
+    },
+    Burn {
+        address: String,
+        shares: Num,
+        quantity: Num,
+    },
+    Swap {
+        asset_in: String,
+        asset_out: String,
+        quantity_in: Num,
+        quantity_out: Num,
+        shares: Num,
+    },
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RebalanceStep {
+    pub action: RebalanceAction,
+    /// Usd value of the trade charged against the budget.
+    pub usd_value: Num,
+    pub fee: Num,
+    pub cashback: Num,
+    /// Sum of asset deviations after the step.
+    pub deviation: Num,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RebalancePlan {
+    pub steps: Vec<RebalanceStep>,
+    /// Pool state after all steps.
+    pub pool: MpPool,
+    pub deviation_before: Num,
+    pub deviation_after: Num,
+    pub spent: Num,
+    pub cashback: Num,
+}
+
+impl MpPool {
+    /// Sum of deviations of every asset from its target share.
+    pub fn total_deviation(&self) -> Num {
+        self.assets
+            .values()
+            .map(|asset| calculate_deviation_mint(Num::ZERO, asset, &self.context))
+            .sum()
+    }
+
+    /// Whether every asset is within the limit or at least not further
+    /// beyond it than in `before`.
+    fn within_limit(&self, before: &MpPool) -> bool {
+        self.assets.iter().all(|(address, asset)| {
+            let deviation = calculate_deviation_mint(Num::ZERO, asset, &self.context);
+            deviation <= self.context.deviation_percent_limit
+                || before.assets.get(address).is_some_and(|old| {
+                    deviation <= calculate_deviation_mint(Num::ZERO, old, &before.context)
+                })
+        })
+    }
+
+    /// Plans trades worth at most `budget` usd that move the pool towards its
+    /// target weights. Every step is the one reducing total deviation the
+    /// most, ties are broken by earned cashback. No step leaves any asset
+    /// beyond the deviation limit, assets already beyond it may only move
+    /// closer to their target. Burns assume the caller holds the shares.
+    pub fn plan_rebalance(&self, budget: Num) -> RebalancePlan {
+        let deviation_before = self.total_deviation();
+        let mut plan = RebalancePlan {
+            steps: Vec::new(),
+            pool: self.clone(),
+            deviation_before,
+            deviation_after: deviation_before,
+            spent: Num::ZERO,
+            cashback: Num::ZERO,
+        };
+        let mut chunk = budget / Num::with_decimals(REBALANCE_CHUNKS.into(), 0);
+        let min_chunk = chunk / Num::with_decimals((1u64 << MAX_HALVINGS).into(), 0);
+        while !chunk.is_zero() && chunk >= min_chunk {
+            let value = chunk.min(budget - plan.spent);
+            if value.is_zero() {
+                break;
+            }
+            let best = plan
+                .pool
+                .rebalance_candidates(value)
+                .into_iter()
+                .filter(|(step, _)| step.deviation < plan.deviation_after)
+                .max_by(|(a, _), (b, _)| {
+                    b.deviation
+                        .cmp(&a.deviation)
+                        .then_with(|| a.cashback.cmp(&b.cashback))
+                });
+            let Some((step, pool)) = best else {
+                chunk /= Num::from("2");
+                continue;
+            };
+            plan.pool = pool;
+            plan.spent += step.usd_value;
+            plan.cashback += step.cashback;
+            plan.deviation_after = step.deviation;
+            plan.steps.push(step);
+        }
+        plan
+    }
+
+    /// Every single trade of `value` usd that keeps all assets within the
+    /// limit, together with the resulting pool.
+    fn rebalance_candidates(&self, value: Num) -> Vec<(RebalanceStep, MpPool)> {
+        let mut candidates = Vec::new();
+        for (address, asset) in &self.assets {
+            let Some(quantity) = value.checked_div(asset.price) else {
+                continue;
+            };
+            candidates.push(self.simulate(value, |pool| {
+                let shares = pool.mint(address, quantity)?;
+                Ok(RebalanceAction::Mint {
+                    address: address.clone(),
+                    quantity,
+                    shares,
+                })
+            }));
+            if let Some(shares) = shares_for_value(
+                value,
+                self.context.total_current_usd_amount,
+                self.total_supply,
+                Rounding::Down,
+            ) {
+                candidates.push(self.simulate(value, |pool| {
+                    let quantity = pool.burn(address, shares)?;
+                    Ok(RebalanceAction::Burn {
+                        address: address.clone(),
+                        shares,
+                        quantity,
+                    })
+                }));
+            }
+            for asset_out in self.assets.keys().filter(|other| *other != address) {
+                candidates.push(self.simulate(value, |pool| {
+                    let (shares, quantity_out) = pool.swap(address, asset_out, quantity)?;
+                    Ok(RebalanceAction::Swap {
+                        asset_in: address.clone(),
+                        asset_out: asset_out.clone(),
+                        quantity_in: quantity,
+                        quantity_out,
+                        shares,
+                    })
+                }));
+            }
+        }
+        candidates.into_iter().flatten().collect()
+    }
+
+    fn simulate<F>(&self, usd_value: Num, trade: F) -> Option<(RebalanceStep, MpPool)>
+    where
+        F: FnOnce(&mut MpPool) -> Result<RebalanceAction, MpError>,
+    {
+        let mut pool = self.clone();
+        let action = trade(&mut pool).ok()?;
+        if !pool.within_limit(self) {
+            return None;
+        }
+        let (fee, cashback) = pool.fees_since(self);
+        let step = RebalanceStep {
+            action,
+            usd_value,
+            fee,
+            cashback,
+            deviation: pool.total_deviation(),
+        };
+        Some((step, pool))
+    }
+}
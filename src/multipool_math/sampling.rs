@@ -0,0 +1,189 @@
+use serde::Serialize;
+
+use super::{calculate_deviation_mint, MpAsset, MpContext, MpOperation};
+use crate::num::{num::Num, snum::SNum};
+
+/// Value varied along the sampled curve.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+pub enum SweepParameter {
+    Amount,
+    CurveCoef,
+    DeviationPercentLimit,
+    OperationBaseFee,
+    Percent,
+}
+
+/// Outcome of a single operation. Quantities are `None` if the operation
+/// fails at this point of the grid.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct FeeSample {
+    pub x: Num,
+    pub quantity_out: Option<Num>,
+    /// Supplied minus utilisable quantity.
+    pub fee: Option<Num>,
+    pub cashback: Option<Num>,
+    /// Deviation of the asset after the operation.
+    pub deviation: Option<Num>,
+    /// Finite-difference derivative of `fee` with respect to `x`.
+    pub fee_derivative: Option<SNum>,
+}
+
+impl FeeSample {
+    fn failed(x: Num) -> Self {
+        Self {
+            x,
+            quantity_out: None,
+            fee: None,
+            cashback: None,
+            deviation: None,
+            fee_derivative: None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct FeeCurve {
+    pub operation: MpOperation,
+    pub parameter: SweepParameter,
+    pub samples: Vec<FeeSample>,
+}
+
+/// `points` evenly spaced values from `from` to `to` inclusive, descending
+/// if `to` is below `from`.
+pub fn linear_grid(from: Num, to: Num, points: u32) -> Vec<Num> {
+    if points < 2 {
+        return vec![from];
+    }
+    let intervals = Num::with_decimals((points - 1).into(), 0);
+    let offset = |i: u32| Num::with_decimals(i.into(), 0);
+    if to < from {
+        let step = (from - to) / intervals;
+        (0..points).map(|i| from - step * offset(i)).collect()
+    } else {
+        let step = (to - from) / intervals;
+        (0..points).map(|i| from + step * offset(i)).collect()
+    }
+}
+
+/// Evaluates `operation` on `amount` at every point of `grid`. With
+/// `SweepParameter::Amount` the grid replaces the amount, otherwise it
+/// replaces the given context or asset parameter.
+pub fn sample_fee_curve(
+    context: &MpContext,
+    asset: &MpAsset,
+    operation: MpOperation,
+    parameter: SweepParameter,
+    amount: Num,
+    grid: &[Num],
+) -> FeeCurve {
+    let mut samples: Vec<FeeSample> = grid
+        .iter()
+        .map(|x| {
+            let mut context = context.clone();
+            let mut asset = asset.clone();
+            let mut amount = amount;
+            match parameter {
+                SweepParameter::Amount => amount = *x,
+                SweepParameter::CurveCoef => context.curve_coef = *x,
+                SweepParameter::DeviationPercentLimit => context.deviation_percent_limit = *x,
+                SweepParameter::OperationBaseFee => context.operation_base_fee = *x,
+                SweepParameter::Percent => {
+                    let Some(total_asset_percents) = context
+                        .total_asset_percents
+                        .checked_sub(asset.percent)
+                        .and_then(|total| total.checked_add(*x))
+                    else {
+                        return FeeSample::failed(*x);
+                    };
+                    context.total_asset_percents = total_asset_percents;
+                    asset.percent = *x;
+                }
+            }
+            sample(context, asset, operation, amount, *x)
+        })
+        .collect();
+
+    for i in 0..samples.len() {
+        let low = i.saturating_sub(1);
+        let high = (i + 1).min(samples.len() - 1);
+        samples[i].fee_derivative = match (&samples[low], &samples[high]) {
+            (
+                FeeSample {
+                    x: x_low,
+                    fee: Some(fee_low),
+                    ..
+                },
+                FeeSample {
+                    x: x_high,
+                    fee: Some(fee_high),
+                    ..
+                },
+            ) if x_high != x_low => Some(
+                (SNum::from(*fee_high) - (*fee_low).into())
+                    / (SNum::from(*x_high) - (*x_low).into()),
+            ),
+            _ => None,
+        };
+    }
+    FeeCurve {
+        operation,
+        parameter,
+        samples,
+    }
+}
+
+fn sample(
+    mut context: MpContext,
+    mut asset: MpAsset,
+    operation: MpOperation,
+    amount: Num,
+    x: Num,
+) -> FeeSample {
+    let cashback_before = context.user_cashback_balance;
+    let result = match operation {
+        MpOperation::Mint => context.mint(&mut asset, amount).ok(),
+        MpOperation::Burn => context.burn(&mut asset, amount).ok(),
+        MpOperation::MintRev => context.mint_rev(&mut asset, amount).ok(),
+        MpOperation::BurnRev => context.burn_rev(&mut asset, amount).ok(),
+        MpOperation::Reconfigure | MpOperation::Payout | MpOperation::Valuation => None,
+    };
+    let Some(quantity_out) = result else {
+        return FeeSample::failed(x);
+    };
+    // reversed operations return the supplied quantity
+    let (supplied, utilisable) = match operation {
+        MpOperation::MintRev | MpOperation::BurnRev => (quantity_out, amount),
+        _ => (amount, quantity_out),
+    };
+    FeeSample {
+        x,
+        quantity_out: Some(quantity_out),
+        fee: supplied.checked_sub(utilisable),
+        cashback: context.user_cashback_balance.checked_sub(cashback_before),
+        deviation: Some(calculate_deviation_mint(Num::ZERO, &asset, &context)),
+        fee_derivative: None,
+    }
+}
+
+impl FeeCurve {
+    pub fn to_csv(&self) -> String {
+        let field = |value: Option<String>| value.unwrap_or_default();
+        let mut csv = String::from("x,quantity_out,fee,cashback,deviation,fee_derivative\n");
+        for sample in &self.samples {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                sample.x,
+                field(sample.quantity_out.map(|v| v.to_string())),
+                field(sample.fee.map(|v| v.to_string())),
+                field(sample.cashback.map(|v| v.to_string())),
+                field(sample.deviation.map(|v| v.to_string())),
+                field(sample.fee_derivative.map(|v| v.to_string())),
+            ));
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+}
@@ -0,0 +1,86 @@
+use std::cmp::Ordering;
+
+use super::{
+    basket::BasketLeg, calculate_deviation_mint, pool::MpPool, MpAsset, MpError, MpOperation,
+};
+use crate::num::{num::Num, snum::SNum};
+
+/// Simulated operation on a single asset.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AssetQuote {
+    pub leg: BasketLeg,
+    /// Usd value of fees minus cashback.
+    pub cost: SNum,
+    pub deviation_before: Num,
+    pub deviation_after: Num,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AssetRank {
+    pub address: String,
+    pub quote: Result<AssetQuote, MpError>,
+}
+
+impl MpPool {
+    /// Ranks every asset by the cost of minting `usd_value` worth of it,
+    /// cheapest first. Assets that can't be minted are placed last.
+    pub fn best_mint_asset(&self, usd_value: Num) -> Vec<AssetRank> {
+        self.rank_assets(MpOperation::Mint, |asset| {
+            usd_value
+                .checked_div(asset.price)
+                .ok_or(MpError::ZeroPrice {
+                    operation: MpOperation::Mint,
+                })
+        })
+    }
+
+    /// Ranks every asset by the cost of burning `shares` into it, cheapest
+    /// first. Assets that can't be burned into are placed last.
+    pub fn best_burn_asset(&self, shares: Num) -> Vec<AssetRank> {
+        self.rank_assets(MpOperation::Burn, |_| Ok(shares))
+    }
+
+    fn rank_assets<F>(&self, operation: MpOperation, amount: F) -> Vec<AssetRank>
+    where
+        F: Fn(&MpAsset) -> Result<Num, MpError>,
+    {
+        let mut ranks: Vec<AssetRank> = self
+            .assets
+            .iter()
+            .map(|(address, asset)| AssetRank {
+                address: address.clone(),
+                quote: amount(asset).and_then(|amount| self.quote(address, amount, operation)),
+            })
+            .collect();
+        ranks.sort_by(|a, b| match (&a.quote, &b.quote) {
+            (Ok(a), Ok(b)) => a.cost.cmp(&b.cost).then_with(|| {
+                let a = SNum::from(a.deviation_after) - a.deviation_before.into();
+                let b = SNum::from(b.deviation_after) - b.deviation_before.into();
+                a.cmp(&b)
+            }),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => Ordering::Equal,
+        });
+        ranks
+    }
+
+    fn quote(
+        &self,
+        address: &str,
+        amount: Num,
+        operation: MpOperation,
+    ) -> Result<AssetQuote, MpError> {
+        let deviation_before =
+            calculate_deviation_mint(Num::ZERO, self.asset(address, operation)?, &self.context);
+        let mut pool = self.clone();
+        let leg = pool.apply_leg(address, amount, operation)?;
+        let asset = &pool.assets[address];
+        Ok(AssetQuote {
+            cost: leg.cost(asset.price),
+            deviation_after: calculate_deviation_mint(Num::ZERO, asset, &pool.context),
+            deviation_before,
+            leg,
+        })
+    }
+}
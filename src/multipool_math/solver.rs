@@ -0,0 +1,279 @@
+use super::{calculate_deviation_burn, calculate_deviation_mint, MpAssetOf, MpContextOf};
+use crate::num::{num::Num, numeric::PoolNumeric};
+
+/// Number of sub-intervals the search range is split into while looking for
+/// a sign change of the fee equation.
+const SCAN_STEPS: u32 = 64;
+
+/// Upper bound of refinement iterations once a root is bracketed. Every
+/// second iteration is a bisection, so the bracket keeps shrinking even when
+/// Newton steps only approach the root from one side.
+const MAX_ITERATIONS: u32 = 512;
+
+/// Side of the target share the asset ends up on after the operation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CurveBranch {
+    AboveTarget,
+    BelowTarget,
+}
+
+/// Which root of the fee equation was picked: one of the closed form
+/// quadratic roots or the numerically refined one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CurveRoot {
+    Minus,
+    Plus,
+    Numeric,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CurveSolution<N = Num> {
+    pub quantity: N,
+    pub branch: CurveBranch,
+    pub root: CurveRoot,
+}
+
+/// Value of a fee equation. The deviation fee grows unbounded as the
+/// deviation approaches the limit, which is reported as an infinity.
+#[derive(Clone, Debug)]
+pub enum Residual<N: PoolNumeric> {
+    Finite(N::Signed),
+    PositiveInfinity,
+    NegativeInfinity,
+}
+
+impl<N: PoolNumeric> Residual<N> {
+    pub fn is_positive(&self) -> bool {
+        match self {
+            Residual::Finite(value) => *value > N::zero().to_signed(),
+            Residual::PositiveInfinity => true,
+            Residual::NegativeInfinity => false,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Residual::Finite(value) => N::abs(value).is_zero(),
+            _ => false,
+        }
+    }
+}
+
+/// Residual of the mint fee equation
+/// `u * (1 + base_fee) + deviation_fee(u) - supplied`.
+pub fn mint_fee_residual<N: PoolNumeric>(
+    utilisable_quantity: N,
+    supplied_quantity: N,
+    asset: &MpAssetOf<N>,
+    context: &MpContextOf<N>,
+) -> Residual<N> {
+    let limit = context.deviation_percent_limit.clone();
+    let deviation = calculate_deviation_mint(utilisable_quantity.clone(), asset, context);
+    if deviation >= limit {
+        return Residual::PositiveInfinity;
+    }
+    let deviation_fee =
+        context.curve_coef.clone() * deviation.clone() * utilisable_quantity.clone()
+            / limit.clone()
+            / (limit - deviation);
+    Residual::Finite(
+        (utilisable_quantity.clone()
+            + utilisable_quantity * context.operation_base_fee.clone()
+            + deviation_fee)
+            .to_signed()
+            - supplied_quantity.to_signed(),
+    )
+}
+
+/// Residual of the burn fee equation
+/// `s - u * (1 + base_fee + fee_ratio(s))`.
+/// Burns draining the pool are reported as negative infinity as well.
+pub fn burn_fee_residual<N: PoolNumeric>(
+    supplied_quantity: N,
+    utilisable_quantity: N,
+    asset: &MpAssetOf<N>,
+    context: &MpContextOf<N>,
+) -> Residual<N> {
+    if supplied_quantity.clone() * asset.price.clone() >= context.total_current_usd_amount {
+        return Residual::NegativeInfinity;
+    }
+    let limit = context.deviation_percent_limit.clone();
+    let deviation = calculate_deviation_burn(supplied_quantity.clone(), asset, context);
+    if deviation >= limit {
+        return Residual::NegativeInfinity;
+    }
+    let fee_ratio =
+        context.curve_coef.clone() * deviation.clone() / limit.clone() / (limit - deviation);
+    Residual::Finite(
+        supplied_quantity.to_signed()
+            - (utilisable_quantity.clone()
+                + utilisable_quantity.clone() * context.operation_base_fee.clone()
+                + utilisable_quantity * fee_ratio)
+                .to_signed(),
+    )
+}
+
+/// Solves the mint fee equation numerically for the utilisable quantity.
+/// The returned quantity never costs more than `supplied_quantity`.
+pub fn solve_mint_numerically<N: PoolNumeric>(
+    supplied_quantity: N,
+    asset: &MpAssetOf<N>,
+    context: &MpContextOf<N>,
+) -> Option<CurveSolution<N>> {
+    let upper = supplied_quantity.clone() / (N::one() + context.operation_base_fee.clone());
+    let residual = |u: &N| mint_fee_residual(u.clone(), supplied_quantity.clone(), asset, context);
+    // the deviation fee may round to zero for dust amounts
+    let low = if !residual(&upper).is_positive() {
+        upper
+    } else {
+        bracketed_root(residual, N::zero(), upper)?.0
+    };
+    if low.is_zero() {
+        return None;
+    }
+    let share = (asset.quantity.clone() + low.clone()) * asset.price.clone()
+        / (context.total_current_usd_amount.clone() + low.clone() * asset.price.clone());
+    Some(CurveSolution {
+        branch: branch_of(share, asset, context),
+        quantity: low,
+        root: CurveRoot::Numeric,
+    })
+}
+
+/// Solves the burn fee equation numerically for the suppliable quantity.
+/// The returned quantity always covers `utilisable_quantity` with fees.
+pub fn solve_burn_numerically<N: PoolNumeric>(
+    utilisable_quantity: N,
+    asset: &MpAssetOf<N>,
+    context: &MpContextOf<N>,
+) -> Option<CurveSolution<N>> {
+    let lower = utilisable_quantity.clone() * (N::one() + context.operation_base_fee.clone());
+    if lower > asset.quantity {
+        return None;
+    }
+    let (_, high) = bracketed_root(
+        |s: &N| burn_fee_residual(s.clone(), utilisable_quantity.clone(), asset, context),
+        lower,
+        asset.quantity.clone(),
+    )?;
+    let share = (asset.quantity.clone() - high.clone()) * asset.price.clone()
+        / (context.total_current_usd_amount.clone() - high.clone() * asset.price.clone());
+    Some(CurveSolution {
+        branch: branch_of(share, asset, context),
+        quantity: high,
+        root: CurveRoot::Numeric,
+    })
+}
+
+fn branch_of<N: PoolNumeric>(
+    share: N,
+    asset: &MpAssetOf<N>,
+    context: &MpContextOf<N>,
+) -> CurveBranch {
+    if share >= asset.percent.clone() / context.total_asset_percents.clone() {
+        CurveBranch::AboveTarget
+    } else {
+        CurveBranch::BelowTarget
+    }
+}
+
+/// Finds the first sign change of `f` on `[low, high]` going from
+/// non-positive to positive values and narrows it with Newton steps,
+/// falling back to bisection whenever a step leaves the bracket.
+/// Returns the final bracket `(a, b)` with `f(a) <= 0 < f(b)`.
+pub fn bracketed_root<N: PoolNumeric, F: Fn(&N) -> Residual<N>>(
+    f: F,
+    low: N,
+    high: N,
+) -> Option<(N, N)> {
+    if high <= low {
+        return None;
+    }
+    let two = N::from_decimal("2");
+    let resolution = N::resolution(&high);
+    let step = (high.clone() - low.clone()) / N::from_decimal(&SCAN_STEPS.to_string());
+
+    let mut a = low;
+    let mut fa = f(&a);
+    if fa.is_zero() {
+        return Some((a.clone(), a));
+    }
+    let mut bracket = None;
+    for i in 1..=SCAN_STEPS {
+        let b = if i == SCAN_STEPS {
+            high.clone()
+        } else {
+            a.clone() + step.clone()
+        };
+        let fb = f(&b);
+        if !fa.is_positive() && fb.is_positive() {
+            bracket = Some((a, fa, b, fb));
+            break;
+        }
+        a = b;
+        fa = fb;
+    }
+    let (mut a, mut fa, mut b, mut fb) = bracket?;
+
+    for iteration in 0..MAX_ITERATIONS {
+        if b.clone() - a.clone() <= resolution {
+            break;
+        }
+        let middle = (a.clone() + (b.clone() - a.clone()) / two.clone()).approximate();
+        let x = if iteration % 2 == 0 {
+            newton_step(&f, (&a, &fa), (&b, &fb), &resolution).unwrap_or(middle)
+        } else {
+            middle
+        };
+        let fx = f(&x);
+        if fx.is_zero() {
+            return Some((x.clone(), x));
+        }
+        if fx.is_positive() {
+            b = x;
+            fb = fx;
+        } else {
+            a = x;
+            fa = fx;
+        }
+    }
+    Some((a, b))
+}
+
+fn newton_step<N: PoolNumeric, F: Fn(&N) -> Residual<N>>(
+    f: &F,
+    (a, fa): (&N, &Residual<N>),
+    (b, fb): (&N, &Residual<N>),
+    resolution: &N,
+) -> Option<N> {
+    let (Residual::Finite(fa), Residual::Finite(fb)) = (fa, fb) else {
+        return None;
+    };
+    // start from the endpoint closer to the root
+    let (x, fx) = if N::abs(fa) < N::abs(fb) {
+        (a, fa)
+    } else {
+        (b, fb)
+    };
+    let mut h = (b.clone() - a.clone()) / N::from_decimal("1024");
+    if h < *resolution {
+        h = resolution.clone();
+    }
+    let probe = if x.clone() + h.clone() < *b {
+        x.clone() + h
+    } else {
+        x.clone() - h
+    };
+    let Residual::Finite(fp) = f(&probe) else {
+        return None;
+    };
+    let slope = (fp - fx.clone()) / (probe.to_signed() - x.to_signed());
+    if N::abs(&slope).is_zero() {
+        return None;
+    }
+    let next = x.to_signed() - fx.clone() / slope;
+    if !(next > a.to_signed() && next < b.to_signed()) {
+        return None;
+    }
+    Some(N::abs(&next).approximate())
+}
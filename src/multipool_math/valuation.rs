@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+
+use super::{
+    pool::{quantity_for_value, value_for_shares, MpPool, Rounding},
+    MpError, MpOperation,
+};
+use crate::num::num::Num;
+
+/// Unit values are expressed in: usd or a quantity of a pool asset.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ValueUnit {
+    Usd,
+    Asset(String),
+}
+
+/// Quantity of an asset standing behind a single share.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AssetBacking {
+    pub quantity: Num,
+    /// Value of `quantity` in the requested unit.
+    pub value: Num,
+}
+
+impl MpPool {
+    /// Net asset value of a single share.
+    pub fn share_price(&self, unit: &ValueUnit) -> Result<Num, MpError> {
+        self.position_value("1".into(), unit)
+    }
+
+    /// Value `shares` would be redeemed at, ignoring fees. Like burns on the
+    /// contract, values are rounded down.
+    pub fn position_value(&self, shares: Num, unit: &ValueUnit) -> Result<Num, MpError> {
+        let operation = MpOperation::Valuation;
+        let value = value_for_shares(
+            shares,
+            self.context.total_current_usd_amount,
+            self.total_supply,
+            Rounding::Down,
+        )
+        .ok_or(MpError::Overflow { operation })?;
+        self.convert(value, unit)
+    }
+
+    /// Quantity of every asset backing a single share, rounded down.
+    pub fn asset_backing_per_share(
+        &self,
+        unit: &ValueUnit,
+    ) -> Result<BTreeMap<String, AssetBacking>, MpError> {
+        let operation = MpOperation::Valuation;
+        self.assets
+            .iter()
+            .map(|(address, asset)| {
+                let quantity = if self.total_supply.is_zero() {
+                    Num::ZERO
+                } else {
+                    asset
+                        .quantity
+                        .mul_div("1".into(), self.total_supply)
+                        .ok_or(MpError::Overflow { operation })?
+                };
+                let value = quantity
+                    .checked_mul(asset.price)
+                    .ok_or(MpError::Overflow { operation })
+                    .and_then(|value| self.convert(value, unit))?;
+                Ok((address.clone(), AssetBacking { quantity, value }))
+            })
+            .collect()
+    }
+
+    fn convert(&self, value: Num, unit: &ValueUnit) -> Result<Num, MpError> {
+        match unit {
+            ValueUnit::Usd => Ok(value),
+            ValueUnit::Asset(address) => {
+                let operation = MpOperation::Valuation;
+                let asset = self.asset(address, operation)?;
+                quantity_for_value(value, asset, Rounding::Down, operation)
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 pub mod num;
+pub mod numeric;
 pub mod snum;
 pub mod uint256;
 pub mod uint512;
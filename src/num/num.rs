@@ -9,6 +9,7 @@ use borsh::{self, BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use super::uint256::{mul_div256, U256};
+use super::uint512::{mul_div512, U512};
 use super::utils::{
     cast_decimals,
     strings::{cast_float_to_integer, cast_integer_to_float},
@@ -66,6 +67,47 @@ impl Num {
         ((self.value * Self::DENOMINATOR).integer_sqrt()).into()
     }
 
+    pub fn checked_add(self, rhs: Self) -> Option<Num> {
+        self.value.checked_add(rhs.value).map(|value| Num { value })
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Num> {
+        self.value.checked_sub(rhs.value).map(|value| Num { value })
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Option<Num> {
+        mul_div256(self.value, rhs.value, Self::DENOMINATOR).map(|value| Num { value })
+    }
+
+    /// Unlike `/` returns `None` on division by zero.
+    pub fn checked_div(self, rhs: Self) -> Option<Num> {
+        if rhs.is_zero() {
+            return None;
+        }
+        mul_div256(self.value, Self::DENOMINATOR, rhs.value).map(|value| Num { value })
+    }
+
+    /// Computes `self * b / c` with a 512-bit intermediate, rounding down.
+    /// Returns `None` on division by zero or if the result does not fit.
+    pub fn mul_div(self, b: Num, c: Num) -> Option<Num> {
+        if c.is_zero() {
+            return None;
+        }
+        mul_div512(self.value, b.value, c.value)
+            .and_then(|value| U256::try_from(value).ok())
+            .map(|value| Num { value })
+    }
+
+    /// Same as `mul_div` but rounds up.
+    pub fn mul_div_up(self, b: Num, c: Num) -> Option<Num> {
+        let down = self.mul_div(b, c)?;
+        let product = U512::from(self.value) * U512::from(b.value);
+        if product % U512::from(c.value) == U512::zero() {
+            return Some(down);
+        }
+        down.checked_add(Num::ONE)
+    }
+
     // Round self value by using the given base.
     // self / base * base
     pub fn round(&self, base: Num) -> Num {
@@ -235,6 +277,25 @@ mod tests {
         assert_eq!(Num::from("0.0016").sqrt(), Num::from("0.04"));
     }
 
+    #[test]
+    pub fn test_mul_div() {
+        let third = Num::from("1").mul_div("1".into(), "3".into()).unwrap();
+        assert_eq!(third.to_string(), "0.333333333333333333333333");
+        let third = Num::from("1").mul_div_up("1".into(), "3".into()).unwrap();
+        assert_eq!(third.to_string(), "0.333333333333333333333334");
+        assert_eq!(
+            Num::from("6").mul_div_up("2".into(), "3".into()),
+            Some("4".into())
+        );
+        // intermediate product does not fit into 256 bits
+        assert_eq!(
+            Num::MAX.mul_div("2".into(), "4".into()),
+            Some(Num::with_decimals(U256::MAX / 2, Num::DECIMALS))
+        );
+        assert_eq!(Num::from("1").mul_div("1".into(), Num::ZERO), None);
+        assert_eq!(Num::MAX.mul_div("2".into(), "1".into()), None);
+    }
+
     #[test]
     pub fn test_from_str() {
         assert_eq!(Num::from("3.333").to_string(), "3.333");
@@ -430,6 +491,18 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn test_checked() {
+        assert_eq!(Num::from("1").checked_add("2".into()), Some("3".into()));
+        assert_eq!(Num::MAX.checked_add(Num::ONE), None);
+        assert_eq!(Num::from("3").checked_sub("2".into()), Some("1".into()));
+        assert_eq!(Num::from("2").checked_sub("3".into()), None);
+        assert_eq!(Num::from("2").checked_mul("3".into()), Some("6".into()));
+        assert_eq!(Num::MAX.checked_mul("2".into()), None);
+        assert_eq!(Num::from("3").checked_div("2".into()), Some("1.5".into()));
+        assert_eq!(Num::from("3").checked_div(Num::ZERO), None);
+    }
+
     #[test]
     pub fn test_with_decimals() {
         assert_eq!(Num::with_decimals(1.into(), 22).value, 100.into());
@@ -0,0 +1,423 @@
+use core::{
+    fmt::{Debug, Display},
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{FromPrimitive, One, Signed, ToPrimitive, Zero};
+
+use super::{num::Num, snum::SNum};
+
+/// Number backend the pool math is generic over.
+///
+/// Values are expected to be non-negative, signed intermediate results of
+/// the curve formulas are carried in `Self::Signed`.
+pub trait PoolNumeric:
+    Clone
+    + PartialOrd
+    + Debug
+    + Display
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+{
+    type Signed: Clone
+        + PartialOrd
+        + Debug
+        + Add<Output = Self::Signed>
+        + Sub<Output = Self::Signed>
+        + Mul<Output = Self::Signed>
+        + Div<Output = Self::Signed>
+        + Neg<Output = Self::Signed>
+        + CheckedSigned;
+
+    fn zero() -> Self;
+
+    /// Parses a plain decimal literal like `"0.0003"`.
+    fn from_decimal(value: &str) -> Self;
+
+    fn is_zero(&self) -> bool;
+
+    fn sqrt(&self) -> Self;
+
+    fn to_signed(&self) -> Self::Signed;
+
+    fn abs(value: &Self::Signed) -> Self;
+
+    fn checked_add(&self, rhs: &Self) -> Option<Self>;
+
+    /// Returns `None` if the result would be negative.
+    fn checked_sub(&self, rhs: &Self) -> Option<Self>;
+
+    fn checked_mul(&self, rhs: &Self) -> Option<Self>;
+
+    /// Returns `None` on division by zero as well.
+    fn checked_div(&self, rhs: &Self) -> Option<Self>;
+
+    /// Smallest bracket width worth refining for values around `scale`.
+    fn resolution(scale: &Self) -> Self;
+
+    /// Bounds the size of the representation, used by iterative solvers.
+    fn approximate(self) -> Self {
+        self
+    }
+
+    fn one() -> Self {
+        Self::from_decimal("1")
+    }
+
+    fn from_num(value: Num) -> Self {
+        Self::from_decimal(&value.to_string())
+    }
+}
+
+impl PoolNumeric for Num {
+    type Signed = SNum;
+
+    fn zero() -> Self {
+        Num::ZERO
+    }
+
+    fn from_decimal(value: &str) -> Self {
+        value.into()
+    }
+
+    fn is_zero(&self) -> bool {
+        Num::is_zero(self)
+    }
+
+    fn sqrt(&self) -> Self {
+        Num::sqrt(self)
+    }
+
+    fn to_signed(&self) -> SNum {
+        (*self).into()
+    }
+
+    fn abs(value: &SNum) -> Self {
+        value.abs()
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        Num::checked_add(*self, *rhs)
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        Num::checked_sub(*self, *rhs)
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        Num::checked_mul(*self, *rhs)
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        Num::checked_div(*self, *rhs)
+    }
+
+    fn resolution(_scale: &Self) -> Self {
+        Num::ONE
+    }
+
+    fn from_num(value: Num) -> Self {
+        value
+    }
+}
+
+impl PoolNumeric for f64 {
+    type Signed = f64;
+
+    fn zero() -> Self {
+        0.0
+    }
+
+    fn from_decimal(value: &str) -> Self {
+        value.parse().expect("invalid decimal literal")
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0.0
+    }
+
+    fn sqrt(&self) -> Self {
+        f64::sqrt(*self)
+    }
+
+    fn to_signed(&self) -> f64 {
+        *self
+    }
+
+    fn abs(value: &f64) -> Self {
+        value.abs()
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        Some(self + rhs).filter(|v| v.is_finite())
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        let value = self - rhs;
+        // absorb rounding noise around zero
+        if value < -(self.abs() + rhs.abs()) * 1e-12 {
+            None
+        } else {
+            Some(value.max(0.0))
+        }
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        Some(self * rhs).filter(|v| v.is_finite())
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        Some(self / rhs).filter(|v| v.is_finite())
+    }
+
+    fn resolution(scale: &Self) -> Self {
+        (scale.abs() * 4.0 * f64::EPSILON).max(f64::MIN_POSITIVE)
+    }
+}
+
+/// Arithmetic of signed intermediates returning `None` on overflow and
+/// division by zero.
+pub trait CheckedSigned: Sized {
+    fn checked_add(&self, rhs: &Self) -> Option<Self>;
+    fn checked_sub(&self, rhs: &Self) -> Option<Self>;
+    fn checked_mul(&self, rhs: &Self) -> Option<Self>;
+    fn checked_div(&self, rhs: &Self) -> Option<Self>;
+}
+
+impl CheckedSigned for SNum {
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        SNum::checked_add(*self, *rhs)
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        SNum::checked_sub(*self, *rhs)
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        SNum::checked_mul(*self, *rhs)
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        SNum::checked_div(*self, *rhs)
+    }
+}
+
+impl CheckedSigned for f64 {
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        Some(self + rhs).filter(|v| v.is_finite())
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        Some(self - rhs).filter(|v| v.is_finite())
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        Some(self * rhs).filter(|v| v.is_finite())
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        Some(self / rhs).filter(|v| v.is_finite())
+    }
+}
+
+impl CheckedSigned for BigRational {
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        Some(self + rhs)
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        Some(self - rhs)
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        Some(self * rhs)
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        (!Zero::is_zero(rhs)).then(|| self / rhs)
+    }
+}
+
+/// Signed intermediate whose operators propagate overflows as `None`, so
+/// checked formulas read like plain ones.
+#[derive(Clone, Debug)]
+pub struct Checked<S>(pub Option<S>);
+
+impl<S> Checked<S> {
+    pub fn new(value: S) -> Self {
+        Self(Some(value))
+    }
+
+    pub fn get(self) -> Option<S> {
+        self.0
+    }
+}
+
+impl<S: CheckedSigned> Add for Checked<S> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0.zip(rhs.0).and_then(|(a, b)| a.checked_add(&b)))
+    }
+}
+
+impl<S: CheckedSigned> Sub for Checked<S> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0.zip(rhs.0).and_then(|(a, b)| a.checked_sub(&b)))
+    }
+}
+
+impl<S: CheckedSigned> Mul for Checked<S> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self(self.0.zip(rhs.0).and_then(|(a, b)| a.checked_mul(&b)))
+    }
+}
+
+impl<S: CheckedSigned> Div for Checked<S> {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        Self(self.0.zip(rhs.0).and_then(|(a, b)| a.checked_div(&b)))
+    }
+}
+
+impl<S: Neg<Output = S>> Neg for Checked<S> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(self.0.map(Neg::neg))
+    }
+}
+
+/// Denominator exponent iterative results are rounded to.
+const RATIONAL_PRECISION_BITS: u32 = 192;
+
+impl PoolNumeric for BigRational {
+    type Signed = BigRational;
+
+    fn zero() -> Self {
+        Zero::zero()
+    }
+
+    fn from_decimal(value: &str) -> Self {
+        let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+        let digits: BigInt = format!("{integer}{fraction}")
+            .parse()
+            .expect("invalid decimal literal");
+        let denominator = BigInt::from(10).pow(fraction.len() as u32);
+        BigRational::new(digits, denominator)
+    }
+
+    fn is_zero(&self) -> bool {
+        Zero::is_zero(self)
+    }
+
+    fn sqrt(&self) -> Self {
+        if Zero::is_zero(self) {
+            return Zero::zero();
+        }
+        let two = BigRational::from_integer(2.into());
+        let mut root = self
+            .to_f64()
+            .map(f64::sqrt)
+            .filter(|v| v.is_normal())
+            .and_then(BigRational::from_f64)
+            .unwrap_or_else(|| self.clone() / two.clone() + <BigRational as One>::one());
+        for _ in 0..64 {
+            let next = ((root.clone() + self.clone() / root.clone()) / two.clone()).approximate();
+            if next == root {
+                break;
+            }
+            root = next;
+        }
+        root
+    }
+
+    fn to_signed(&self) -> BigRational {
+        self.clone()
+    }
+
+    fn abs(value: &BigRational) -> Self {
+        Signed::abs(value)
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        Some(self + rhs)
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        Some(self - rhs).filter(|v| !v.is_negative())
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        Some(self * rhs)
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        (!Zero::is_zero(rhs)).then(|| self / rhs)
+    }
+
+    fn resolution(scale: &Self) -> Self {
+        let unit = BigRational::new(One::one(), BigInt::from(2).pow(RATIONAL_PRECISION_BITS / 2));
+        (Signed::abs(scale) + <BigRational as One>::one()) * unit
+    }
+
+    fn approximate(self) -> Self {
+        let denominator = BigInt::from(2).pow(RATIONAL_PRECISION_BITS);
+        let numerator = (self * BigRational::from_integer(denominator.clone()))
+            .round()
+            .to_integer();
+        BigRational::new(numerator, denominator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_from_decimal() {
+        assert_eq!(
+            <Num as PoolNumeric>::from_decimal("0.0003"),
+            Num::from("0.0003")
+        );
+        assert_eq!(<f64 as PoolNumeric>::from_decimal("0.0003"), 0.0003);
+        assert_eq!(
+            <BigRational as PoolNumeric>::from_decimal("0.0003"),
+            BigRational::new(3.into(), 10000.into())
+        );
+        assert_eq!(
+            <BigRational as PoolNumeric>::from_num("12.5".into()),
+            BigRational::new(25.into(), 2.into())
+        );
+    }
+
+    #[test]
+    pub fn test_sqrt() {
+        let value = <BigRational as PoolNumeric>::from_decimal("0.0016");
+        let root = PoolNumeric::sqrt(&value);
+        let error = Signed::abs(&(root - BigRational::new(4.into(), 100.into())));
+        assert!(error < BigRational::new(1.into(), BigInt::from(10).pow(40)));
+        assert_eq!(PoolNumeric::sqrt(&16.0f64), 4.0);
+    }
+
+    #[test]
+    pub fn test_checked_sub() {
+        assert_eq!(PoolNumeric::checked_sub(&Num::from("1"), &"2".into()), None);
+        assert_eq!(PoolNumeric::checked_sub(&1.0f64, &2.0), None);
+        assert_eq!(PoolNumeric::checked_sub(&0.3f64, &(0.1 + 0.2)), Some(0.0));
+        let one = <BigRational as PoolNumeric>::from_decimal("1");
+        assert_eq!(
+            PoolNumeric::checked_sub(&one, &(one.clone() + one.clone())),
+            None
+        );
+    }
+}
@@ -71,6 +71,36 @@ impl SNum {
     pub fn is_zero(&self) -> bool {
         self.value == 0.into()
     }
+
+    pub fn checked_add(self, rhs: Self) -> Option<SNum> {
+        if self.is_negative != rhs.is_negative {
+            // magnitudes are subtracted, which can't overflow
+            return Some(self + rhs);
+        }
+        Some(Self {
+            value: self.value.checked_add(rhs.value)?,
+            is_negative: self.is_negative,
+        })
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<SNum> {
+        self.checked_add(-rhs)
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Option<SNum> {
+        Some(Self {
+            value: self.value.checked_mul(rhs.value)?,
+            is_negative: self.is_negative ^ rhs.is_negative,
+        })
+    }
+
+    /// Unlike `/` returns `None` on division by zero.
+    pub fn checked_div(self, rhs: Self) -> Option<SNum> {
+        Some(Self {
+            value: self.value.checked_div(rhs.value)?,
+            is_negative: self.is_negative ^ rhs.is_negative,
+        })
+    }
 }
 
 impl From<Num> for SNum {
@@ -399,6 +429,18 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn test_checked() {
+        let two = SNum::from("2");
+        assert_eq!(SNum::MIN.checked_add(SNum::MAX), Some(SNum::ZERO));
+        assert_eq!(SNum::MIN.checked_sub(two), None);
+        assert_eq!((-two).checked_sub(two), Some("-4".into()));
+        assert_eq!(SNum::MAX.checked_mul(-two), None);
+        assert_eq!((-two).checked_mul(two), Some("-4".into()));
+        assert_eq!(two.checked_div(SNum::ZERO), None);
+        assert_eq!(two.checked_div(-two), Some("-1".into()));
+    }
+
     #[test]
     pub fn test_serde() {
         fn test_serde(num: SNum) {
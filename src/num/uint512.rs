@@ -20,3 +20,24 @@ pub fn mul_div512<A: Into<U512>, B: Into<U512>, C: Into<U512>>(a: A, b: B, c: C)
         a.checked_mul(b).and_then(|val| val.checked_div(c))
     }
 }
+
+impl From<super::uint256::U256> for U512 {
+    fn from(value: super::uint256::U256) -> Self {
+        let mut words = [0; 8];
+        words[..4].copy_from_slice(&value.0);
+        U512(words)
+    }
+}
+
+impl TryFrom<U512> for super::uint256::U256 {
+    type Error = ();
+
+    fn try_from(value: U512) -> Result<Self, Self::Error> {
+        if value.0[4..].iter().any(|word| *word != 0) {
+            return Err(());
+        }
+        let mut words = [0; 4];
+        words.copy_from_slice(&value.0[..4]);
+        Ok(super::uint256::U256(words))
+    }
+}
@@ -1,43 +1,129 @@
-use crate::multipool_math::actions::adapter::{MockedAdapter, MpAdapter};
-use crate::multipool_math::{MpAsset, MpContext};
+use crate::multipool_math::actions::adapter::MpAdapter;
+use crate::multipool_math::actions::error::ActionError;
+use crate::multipool_math::actions::DeadlineKind;
+use crate::multipool_math::{MpError, MpOperation};
+use crate::tests::common::adapter;
+use std::time::Duration;
 
 #[test]
 fn mint() {
-    let connection = MockedAdapter {
-        assets: [(
-            String::from("0x345"),
-            MpAsset {
-                quantity: "50".into(),
-                price: "10".into(),
-                collected_fees: "0".into(),
-                collected_cashbacks: "0".into(),
-                percent: "50".into(),
-            },
-        )]
-        .into_iter()
-        .collect(),
-        context: MpContext {
-            total_current_usd_amount: "1000".into(),
-            total_asset_percents: "100".into(),
-            curve_coef: "0.0003".into(),
-            deviation_percent_limit: "0.1".into(),
-            operation_base_fee: "0.0001".into(),
-            user_cashback_balance: "0".into(),
-        },
-        total_supply: "100".into(),
-        current_block: "100".into(),
-    };
     // send multipool transaction
-    let val = connection
+    let val = adapter()
         .configure()
         .amount_out("10") // enter amount in or amount out
         .slippage_percent("10") // specify slippage percent
         .pool("0x123") // set multipool address
-        .asset_in("0x345") // select token
+        .asset_in("0xa") // select token
         .receiver("0xME") // select token
         .blocks_to_live("10") // add deadlines (ttl)
         .fetch() // update context and assets
+        .unwrap()
         .mint() // specify txn type
-        .send_mint(); // send transaction via connection
+        .unwrap()
+        .send_mint() // send transaction via connection
+        .unwrap();
     println!("{val:?}");
 }
+
+#[test]
+fn missing_settings_are_reported() {
+    let error = adapter().configure().asset_in("0xa").fetch().unwrap_err();
+    assert_eq!(error, ActionError::MissingField("pool address"));
+    assert_eq!(error.to_string(), "pool address not set");
+
+    let error = adapter()
+        .configure()
+        .pool("0x123")
+        .asset_in("0xa")
+        .fetch()
+        .unwrap()
+        .mint()
+        .unwrap_err();
+    assert_eq!(
+        error,
+        ActionError::MissingField("sending or receiving amount")
+    );
+
+    let mut settings = adapter().configure();
+    assert_eq!(
+        settings.send_burn().unwrap_err(),
+        ActionError::MissingField("burn params")
+    );
+}
+
+#[test]
+fn adapter_and_math_errors_are_returned() {
+    let error = adapter()
+        .configure()
+        .pool("0x123")
+        .asset_in("0x999")
+        .fetch()
+        .unwrap_err();
+    assert!(matches!(error, ActionError::Adapter(_)));
+
+    // burning more shares than issued
+    let error = adapter()
+        .configure()
+        .amount_in("1000")
+        .pool("0x123")
+        .asset_out("0xa")
+        .receiver("0xME")
+        .fetch()
+        .unwrap()
+        .burn()
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        ActionError::Math(MpError::InsufficientShares {
+            operation: MpOperation::Burn,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn deadlines_record_their_kind() {
+    let settings = adapter()
+        .configure()
+        .amount_in("1")
+        .pool("0x123")
+        .asset_in("0xa")
+        .receiver("0xME")
+        .fetch()
+        .unwrap();
+
+    let params = settings.clone().mint().unwrap().mint_params.unwrap();
+    assert_eq!(
+        (params.deadline, params.deadline_kind),
+        (0.into(), DeadlineKind::None)
+    );
+
+    let params = settings
+        .clone()
+        .blocks_to_live("10")
+        .mint()
+        .unwrap()
+        .mint_params
+        .unwrap();
+    assert_eq!(params.deadline, "110".into());
+    assert_eq!(params.deadline_kind, DeadlineKind::Block);
+
+    let params = settings
+        .clone()
+        .until_timestamp("1700000600")
+        .mint()
+        .unwrap()
+        .mint_params
+        .unwrap();
+    assert_eq!(params.deadline, "1700000600".into());
+    assert_eq!(params.deadline_kind, DeadlineKind::Timestamp);
+
+    let params = settings
+        .time_to_live(Duration::from_secs(300))
+        .mint()
+        .unwrap()
+        .mint_params
+        .unwrap();
+    assert_eq!(params.deadline, "1700000300".into());
+    assert_eq!(params.deadline_kind, DeadlineKind::Timestamp);
+}
@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+use crate::{
+    multipool_math::actions::{
+        adapter::MpAdapter,
+        approval::{ApproveTxnParams, PermitSignature},
+    },
+    tests::common::adapter,
+};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn missing_allowance_is_approved_once() {
+    let mut settings = adapter()
+        .configure()
+        .amount_out("5")
+        .pool("0x123")
+        .asset_in("0xa")
+        .receiver("0xME")
+        .slippage_percent("0.01")
+        .fetch()
+        .unwrap()
+        .mint()
+        .unwrap();
+    let amount_in_max = settings.mint_params.as_ref().unwrap().amount_in_max;
+    assert_eq!(
+        settings.ensure_allowance().unwrap(),
+        Some(ApproveTxnParams {
+            token_address: "0xa".into(),
+            spender_address: "0x123".into(),
+            amount: amount_in_max,
+        })
+    );
+    assert_eq!(settings.ensure_allowance().unwrap(), None);
+    assert_eq!(settings.mint_params.unwrap().permit, None);
+}
+
+#[test]
+fn permit_is_bundled_with_swap_params() {
+    let mut settings = adapter()
+        .configure()
+        .amount_in("2")
+        .pool("0x123")
+        .asset_in("0xa")
+        .asset_out("0xb")
+        .receiver("0xME")
+        .time_to_live(Duration::from_secs(60))
+        .use_permit()
+        .fetch()
+        .unwrap()
+        .swap()
+        .unwrap();
+    assert_eq!(settings.ensure_allowance().unwrap(), None);
+    let permit = settings
+        .swap_params
+        .as_ref()
+        .unwrap()
+        .permit
+        .clone()
+        .unwrap();
+    assert_eq!(permit.params.token_address, "0xa");
+    assert_eq!(permit.params.spender_address, "0x123");
+    assert_eq!(permit.params.value, "2".into());
+    assert_eq!(permit.params.deadline, "1700000060".into());
+    assert_eq!(
+        permit.signature,
+        PermitSignature {
+            v: 27,
+            r: [1; 32],
+            s: [2; 32],
+        }
+    );
+    assert_eq!(settings.quote().unwrap().permit, Some(permit));
+    let sent = settings.send_swap().unwrap();
+    assert!(sent.permit.is_some());
+}
+
+#[test]
+fn permits_without_timestamp_deadline_expire_after_ttl() {
+    let mut settings = adapter()
+        .configure_typed()
+        .pool("0x123")
+        .asset_in("0xa")
+        .amount_in("2")
+        .receiver("0xME")
+        .blocks_to_live("10")
+        .use_permit()
+        .fetch()
+        .unwrap()
+        .mint()
+        .unwrap();
+    settings.ensure_allowance().unwrap();
+    let permit = settings.send().unwrap().permit.unwrap();
+    assert_eq!(permit.params.deadline, "1700001800".into());
+}
+
+#[test]
+fn burns_and_sufficient_allowances_need_no_approval() {
+    let mut settings = adapter()
+        .configure()
+        .amount_in("1")
+        .pool("0x123")
+        .asset_out("0xb")
+        .receiver("0xME")
+        .fetch()
+        .unwrap()
+        .burn()
+        .unwrap();
+    assert_eq!(settings.ensure_allowance().unwrap(), None);
+
+    let mut adapter = adapter();
+    adapter
+        .allowances
+        .insert(("0xa".into(), "0x123".into()), "2".into());
+    let mut settings = adapter
+        .configure()
+        .amount_in("2")
+        .pool("0x123")
+        .asset_in("0xa")
+        .receiver("0xME")
+        .use_permit()
+        .fetch()
+        .unwrap()
+        .mint()
+        .unwrap();
+    assert_eq!(settings.ensure_allowance().unwrap(), None);
+    assert_eq!(settings.quote().unwrap().permit, None);
+}
+
+#[test]
+fn allowances_of_other_spenders_are_ignored() {
+    let mut adapter = adapter();
+    adapter
+        .allowances
+        .insert(("0xa".into(), "0x456".into()), "2".into());
+    let mut settings = adapter
+        .configure()
+        .amount_in("2")
+        .pool("0x123")
+        .asset_in("0xa")
+        .receiver("0xME")
+        .fetch()
+        .unwrap()
+        .mint()
+        .unwrap();
+    let approval = settings.ensure_allowance().unwrap().unwrap();
+    assert_eq!(approval.spender_address, "0x123");
+}
@@ -0,0 +1,168 @@
+use std::future::{poll_fn, Future};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::task::Poll;
+use std::thread;
+
+use futures::executor::block_on;
+
+use crate::{
+    multipool_math::{
+        actions::{
+            adapter::MpAdapter,
+            async_adapter::AsyncMpAdapter,
+            error::ActionError,
+            settings::{BurnTxnParams, MintTxnParams, SwapTxnParams},
+            DeadlineKind,
+        },
+        MpAsset, MpContext,
+    },
+    num::num::Num,
+    tests::common::{adapter, asset, context},
+};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn bridged_sync_adapter_matches_sync_flow() {
+    let expected = adapter()
+        .configure()
+        .amount_in("2")
+        .pool("0x123")
+        .asset_in("0xa")
+        .asset_out("0xb")
+        .receiver("0xME")
+        .blocks_to_live("10")
+        .fetch()
+        .unwrap()
+        .swap()
+        .unwrap()
+        .swap_params
+        .unwrap();
+    let params = block_on(async {
+        let settings = Mutex::new(adapter())
+            .configure_async()
+            .amount_in("2")
+            .pool("0x123")
+            .asset_in("0xa")
+            .asset_out("0xb")
+            .receiver("0xME")
+            .blocks_to_live("10")
+            .fetch_async()
+            .await?
+            .swap_async()
+            .await?;
+        settings.send_swap_async().await
+    })
+    .unwrap();
+    assert_eq!(params.amount_out_min, expected.amount_out_min);
+    assert_eq!(params.shares, expected.shares);
+    assert_eq!(params.deadline, "110".into());
+    assert_eq!(params.deadline_kind, DeadlineKind::Block);
+}
+
+#[test]
+fn bridged_actions_run_on_other_threads() {
+    let action = async {
+        Mutex::new(adapter())
+            .configure_async()
+            .amount_in("2")
+            .pool("0x123")
+            .asset_in("0xa")
+            .asset_out("0xb")
+            .receiver("0xME")
+            .fetch_async()
+            .await?
+            .swap_async()
+            .await
+    };
+    let settings = thread::spawn(move || block_on(action))
+        .join()
+        .unwrap()
+        .unwrap();
+    assert!(settings.swap_params.is_some());
+}
+
+#[test]
+fn bridged_adapter_errors_are_returned() {
+    let error = block_on(
+        Mutex::new(adapter())
+            .configure_async()
+            .pool("0x123")
+            .asset_in("0xc")
+            .fetch_async(),
+    )
+    .err()
+    .unwrap();
+    assert!(matches!(error, ActionError::Adapter(_)));
+}
+
+/// Adapter whose requests only complete once all four fetches are started.
+struct BarrierAdapter {
+    started: AtomicU32,
+}
+
+impl BarrierAdapter {
+    fn wait<'a, T: Send + 'a>(
+        &'a self,
+        value: T,
+    ) -> impl Future<Output = Result<T, String>> + Send + 'a {
+        self.started.fetch_add(1, Ordering::SeqCst);
+        let mut value = Some(value);
+        let mut polls = 0;
+        poll_fn(move |cx| {
+            polls += 1;
+            if self.started.load(Ordering::SeqCst) >= 4 {
+                Poll::Ready(Ok(value.take().unwrap()))
+            } else if polls > 100 {
+                Poll::Ready(Err("requests are not concurrent".to_owned()))
+            } else {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+    }
+}
+
+impl AsyncMpAdapter for BarrierAdapter {
+    type Error = String;
+    type MintTxnResult = ();
+    type BurnTxnResult = ();
+    type SwapTxnResult = ();
+
+    async fn get_context(&self, _address: &str) -> Result<MpContext, Self::Error> {
+        self.wait(context()).await
+    }
+    async fn get_total_supply(&self, _address: &str) -> Result<Num, Self::Error> {
+        self.wait("100".into()).await
+    }
+    async fn get_asset(&self, _address: &str, _asset: &str) -> Result<MpAsset, Self::Error> {
+        self.wait(asset("50", "10")).await
+    }
+    async fn get_current_block(&self) -> Result<Num, Self::Error> {
+        Ok("100".into())
+    }
+    async fn get_current_timestamp(&self) -> Result<Num, Self::Error> {
+        Ok("1700000000".into())
+    }
+    async fn transact_mint(&self, _params: &MintTxnParams) {}
+    async fn transact_burn(&self, _params: &BurnTxnParams) {}
+    async fn transact_swap(&self, _params: &SwapTxnParams) {}
+}
+
+#[test]
+fn fetches_run_concurrently() {
+    let adapter = BarrierAdapter {
+        started: AtomicU32::new(0),
+    };
+    let settings = block_on(
+        adapter
+            .configure_async()
+            .pool("0x123")
+            .asset_in("0xa")
+            .asset_out("0xb")
+            .fetch_async(),
+    )
+    .unwrap();
+    assert_eq!(settings.context, Some(context()));
+    assert!(settings.asset_in.is_some() && settings.asset_out.is_some());
+}
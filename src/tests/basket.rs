@@ -0,0 +1,80 @@
+use crate::{
+    multipool_math::MpError,
+    num::num::Num,
+    tests::common::{pool, skewed_pool},
+};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn mint_basket_issues_shares_for_every_leg() {
+    let mut pool = pool();
+    let outcome = pool
+        .mint_basket(&[("0xa", "5".into()), ("0xb", "2.5".into())])
+        .unwrap();
+    assert_eq!(outcome.legs.len(), 2);
+    assert!(outcome.shares < "10".into() && outcome.shares > "9.99".into());
+    assert_eq!(pool.total_supply, Num::from("100") + outcome.shares);
+    for leg in &outcome.legs {
+        assert!(!leg.base_fee.is_zero());
+        assert_eq!(pool.assets[&leg.address].collected_fees, leg.base_fee);
+    }
+}
+
+#[test]
+fn mint_basket_applies_rebalancing_leg_first() {
+    // 0xa is under its target share
+    let mut pool = skewed_pool("1");
+    let outcome = pool
+        .mint_basket(&[("0xb", "1".into()), ("0xa", "2".into())])
+        .unwrap();
+    assert_eq!(outcome.legs[0].address, "0xa");
+    assert!(!outcome.legs[0].cashback.is_zero());
+    assert!(outcome.legs[0].deviation_fee.is_zero());
+    assert!(!outcome.legs[1].deviation_fee.is_zero());
+}
+
+#[test]
+fn burn_proportional_keeps_composition() {
+    let mut pool = pool();
+    let outcome = pool.burn_proportional("10".into()).unwrap();
+    assert_eq!(outcome.shares, "10".into());
+    assert_eq!(pool.total_supply, "90".into());
+    assert_eq!(pool.assets["0xa"].quantity, "45".into());
+    assert_eq!(pool.assets["0xb"].quantity, "22.5".into());
+    let a = outcome
+        .legs
+        .iter()
+        .find(|leg| leg.address == "0xa")
+        .unwrap();
+    assert!(a.quantity < "5".into() && a.quantity > "4.99".into());
+}
+
+#[test]
+fn burn_basket_splits_shares_by_weight() {
+    let mut pool = pool();
+    let outcome = pool
+        .burn_basket("8".into(), &[("0xa", "3".into()), ("0xb", "1".into())])
+        .unwrap();
+    let shares = |address: &str| {
+        outcome
+            .legs
+            .iter()
+            .find(|leg| leg.address == address)
+            .unwrap()
+            .shares
+    };
+    assert_eq!(shares("0xa"), "6".into());
+    assert_eq!(shares("0xb"), "2".into());
+    assert_eq!(pool.total_supply, "92".into());
+}
+
+#[test]
+fn failed_basket_leaves_pool_untouched() {
+    let mut pool = pool();
+    let result = pool.mint_basket(&[("0xa", "1".into()), ("0xc", "1".into())]);
+    assert!(matches!(result, Err(MpError::UnknownAsset { .. })));
+    assert_eq!(pool, self::pool());
+
+    let result = pool.burn_basket("1".into(), &[("0xa", Num::ZERO)]);
+    assert!(matches!(result, Err(MpError::EmptyBasket { .. })));
+}
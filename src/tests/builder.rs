@@ -0,0 +1,88 @@
+use crate::{
+    multipool_math::actions::{adapter::MpAdapter, error::ActionError},
+    num::num::Num,
+    tests::common::adapter,
+};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn typed_mint_matches_dynamic_builder() {
+    let params = adapter()
+        .configure_typed()
+        .pool("0x123")
+        .asset_in("0xa")
+        .amount_out("10")
+        .slippage_percent("0.01")
+        .blocks_to_live("10")
+        .fetch()
+        .unwrap()
+        .receiver("0xME")
+        .mint()
+        .unwrap()
+        .send()
+        .unwrap();
+    let expected = adapter()
+        .configure()
+        .pool("0x123")
+        .asset_in("0xa")
+        .amount_out("10")
+        .slippage_percent("0.01")
+        .blocks_to_live("10")
+        .receiver("0xME")
+        .fetch()
+        .unwrap()
+        .mint()
+        .unwrap()
+        .mint_params
+        .unwrap();
+    assert_eq!(params.shares, expected.shares);
+    assert_eq!(params.amount_in_max, expected.amount_in_max);
+    assert_eq!(params.deadline, "110".into());
+}
+
+#[test]
+fn typed_burn_and_swap() {
+    let burn = adapter()
+        .configure_typed()
+        .receiver("0xME")
+        .pool("0x123")
+        .asset_out("0xb")
+        .amount_in("1")
+        .fetch()
+        .unwrap()
+        .burn()
+        .unwrap();
+    assert!(burn.settings().price_impact.is_some());
+    let params = burn.send().unwrap();
+    assert_eq!(params.asset_out_address, "0xb");
+    assert!(params.amount_out_min > Num::ZERO);
+
+    let params = adapter()
+        .configure_typed()
+        .pool("0x123")
+        .asset_out("0xb")
+        .asset_in("0xa")
+        .receiver("0xME")
+        .amount_in("2")
+        .fetch()
+        .unwrap()
+        .swap()
+        .unwrap()
+        .send()
+        .unwrap();
+    assert_eq!(params.asset_in_address, "0xa");
+    assert_eq!(params.amount_in_max, "2".into());
+}
+
+#[test]
+fn typed_builder_reports_adapter_errors() {
+    let error = adapter()
+        .configure_typed()
+        .pool("0x123")
+        .asset_in("0xc")
+        .amount_in("1")
+        .fetch()
+        .err()
+        .unwrap();
+    assert!(matches!(error, ActionError::Adapter(_)));
+}
@@ -0,0 +1,57 @@
+use crate::multipool_math::{actions::adapter::MockedAdapter, pool::MpPool, MpAsset, MpContext};
+
+/// Context of a pool worth 1000 usd.
+pub fn context() -> MpContext {
+    MpContext {
+        total_current_usd_amount: "1000".into(),
+        total_asset_percents: "100".into(),
+        curve_coef: "0.0003".into(),
+        deviation_percent_limit: "0.1".into(),
+        operation_base_fee: "0.0001".into(),
+        user_cashback_balance: "0".into(),
+    }
+}
+
+/// Asset targeting half of the pool, nothing collected yet.
+pub fn asset(quantity: &str, price: &str) -> MpAsset {
+    MpAsset {
+        quantity: quantity.into(),
+        price: price.into(),
+        collected_fees: "0".into(),
+        collected_cashbacks: "0".into(),
+        percent: "50".into(),
+    }
+}
+
+/// Pool of 100 shares at its target weights, 0xa and 0xb each holding half.
+pub fn pool() -> MpPool {
+    MpPool::new(context(), "100".into())
+        .with_asset("0xa", asset("50", "10"))
+        .with_asset("0xb", asset("25", "20"))
+}
+
+/// Pool of 100 shares off its target weights, every asset holding
+/// `cashback` of collected cashbacks.
+pub fn skewed_pool(cashback: &str) -> MpPool {
+    let asset = |quantity, price| MpAsset {
+        collected_cashbacks: cashback.into(),
+        ..asset(quantity, price)
+    };
+    // 0xa holds 45% of the pool, 0xb holds 55%
+    MpPool::new(context(), "100".into())
+        .with_asset("0xa", asset("45", "10"))
+        .with_asset("0xb", asset("27.5", "20"))
+}
+
+/// Adapter serving `pool` at block 100.
+pub fn adapter() -> MockedAdapter {
+    let pool = pool();
+    MockedAdapter {
+        assets: pool.assets.into_iter().collect(),
+        context: pool.context,
+        total_supply: pool.total_supply,
+        current_block: "100".into(),
+        current_timestamp: "1700000000".into(),
+        allowances: Default::default(),
+    }
+}
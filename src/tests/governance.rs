@@ -0,0 +1,81 @@
+use crate::{
+    multipool_math::{
+        governance::{CashbackIncentive, ParameterChange, ReferenceTrade},
+        MpError, MpOperation,
+    },
+    num::num::Num,
+    tests::common::skewed_pool,
+};
+use pretty_assertions::assert_eq;
+
+fn mint(address: &str, amount: &str) -> ReferenceTrade {
+    ReferenceTrade {
+        address: address.into(),
+        operation: MpOperation::Mint,
+        amount: amount.into(),
+    }
+}
+
+#[test]
+fn tightening_limit_puts_assets_out_of_bounds() {
+    let report = skewed_pool("0")
+        .simulate_changes(
+            &[ParameterChange::DeviationPercentLimit("0.04".into())],
+            &[mint("0xb", "1")],
+        )
+        .unwrap();
+    assert!(report
+        .assets
+        .iter()
+        .all(|asset| !asset.out_of_bounds_before && asset.out_of_bounds_after));
+    // minting the overweight asset is not possible anymore
+    assert!(report.trades[0].fee_before.is_some());
+    assert_eq!(report.trades[0].fee_after, None);
+}
+
+#[test]
+fn percent_change_flips_incentives() {
+    let change = ParameterChange::AssetPercent {
+        address: "0xa".into(),
+        percent: "30".into(),
+    };
+    let report = skewed_pool("0").simulate_changes(&[change], &[]).unwrap();
+    assert_eq!(report.pool.context.total_asset_percents, "80".into());
+    let a = &report.assets[0];
+    assert_eq!(a.incentive_before, CashbackIncentive::Mint);
+    assert_eq!(a.incentive_after, CashbackIncentive::Burn);
+    assert_eq!(a.deviation_after, "0.075".into());
+}
+
+#[test]
+fn coefficient_change_moves_reference_fees() {
+    let report = skewed_pool("0")
+        .simulate_changes(
+            &[
+                ParameterChange::CurveCoef("0.003".into()),
+                ParameterChange::OperationBaseFee("0.001".into()),
+            ],
+            &[mint("0xb", "1"), mint("0xa", "1")],
+        )
+        .unwrap();
+    for trade in &report.trades {
+        assert!(trade.fee_after.unwrap() > trade.fee_before.unwrap());
+    }
+    // the report carries the changed pool
+    assert_eq!(report.pool.context.curve_coef, "0.003".into());
+}
+
+#[test]
+fn change_of_unknown_asset_fails() {
+    let change = ParameterChange::AssetPercent {
+        address: "0xc".into(),
+        percent: Num::ZERO,
+    };
+    assert!(matches!(
+        skewed_pool("0").simulate_changes(&[change], &[]),
+        Err(MpError::UnknownAsset {
+            operation: MpOperation::Reconfigure,
+            ..
+        })
+    ));
+}
@@ -0,0 +1,56 @@
+use crate::{
+    multipool_math::governance::CashbackIncentive, num::num::Num, tests::common::skewed_pool,
+};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn health_lists_every_asset() {
+    let health = skewed_pool("0.5").health();
+    let a = &health.assets[0];
+    assert_eq!(a.current_share, "0.45".into());
+    assert_eq!(a.target_share, "0.5".into());
+    assert_eq!(a.deviation, "0.05".into());
+    assert_eq!(a.headroom, "0.05".into());
+    assert_eq!(a.pending_cashback, "0.5".into());
+    assert_eq!(a.incentive, CashbackIncentive::Mint);
+    assert_eq!(health.assets[1].incentive, CashbackIncentive::Burn);
+    assert_eq!(health.score, "0.5".into());
+}
+
+#[test]
+fn balanced_pool_is_fully_healthy() {
+    let mut pool = skewed_pool("0.5");
+    pool.assets.get_mut("0xa").unwrap().quantity = "50".into();
+    pool.assets.get_mut("0xb").unwrap().quantity = "25".into();
+    let health = pool.health();
+    assert_eq!(health.score, "1".into());
+    assert!(health
+        .assets
+        .iter()
+        .all(|asset| asset.incentive == CashbackIncentive::None));
+}
+
+#[test]
+fn asset_beyond_limit_has_no_headroom() {
+    let mut pool = skewed_pool("0.5");
+    pool.context.deviation_percent_limit = "0.04".into();
+    let health = pool.health();
+    assert!(health.assets.iter().all(|asset| asset.headroom.is_zero()));
+    assert_eq!(health.score, Num::ZERO);
+}
+
+#[test]
+fn health_prints_as_table() {
+    let table = skewed_pool("0.5").health().to_string();
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("asset  share  target  deviation"));
+    assert!(lines[1].starts_with("0xa    0.45   0.5     0.05"));
+    assert!(lines[1].ends_with("Mint"));
+    assert_eq!(lines[3], "score: 0.5");
+    assert!(skewed_pool("0.5")
+        .health()
+        .to_json()
+        .unwrap()
+        .contains("\"incentive\":\"Burn\""));
+}
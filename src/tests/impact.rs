@@ -0,0 +1,73 @@
+use crate::{
+    multipool_math::{
+        actions::adapter::MpAdapter, impact::PriceImpact, pool::MpPool, MpError, MpOperation,
+    },
+    num::snum::SNum,
+    tests::common::{adapter, asset, context},
+};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn mint_impact_against_share_price() {
+    let pool = MpPool::new(context(), "100".into()).with_asset("0xa", asset("50", "10"));
+    let impact = PriceImpact::mint(&pool, "0xa", "5.0051875".into(), "5".into()).unwrap();
+    assert_eq!(impact.mid_price, "10".into());
+    assert_eq!(impact.effective_price, "10.010375".into());
+    assert_eq!(impact.impact_bps, "10.375".into());
+}
+
+#[test]
+fn burn_and_swap_impact_is_positive_when_receiving_less() {
+    let pool = MpPool::new(context(), "100".into())
+        .with_asset("0xa", asset("50", "10"))
+        .with_asset("0xb", asset("25", "20"));
+    let impact = PriceImpact::burn(&pool, "0xa", "1".into(), "0.999".into()).unwrap();
+    assert_eq!(impact.effective_price, "9.99".into());
+    assert_eq!(impact.impact_bps, "10".into());
+
+    let impact = PriceImpact::swap(&pool, "0xa", "0xb", "2".into(), "1.001".into()).unwrap();
+    assert_eq!(impact.mid_price, "0.5".into());
+    assert_eq!(impact.impact_bps, SNum::from("-10"));
+}
+
+#[test]
+fn impact_rejects_unknown_assets_and_zero_amounts() {
+    let pool = MpPool::new(context(), "100".into()).with_asset("0xa", asset("50", "10"));
+    assert_eq!(
+        PriceImpact::mint(&pool, "0xc", "1".into(), "1".into()),
+        Err(MpError::UnknownAsset {
+            operation: MpOperation::Mint,
+            address: "0xc".into(),
+        })
+    );
+    assert_eq!(
+        PriceImpact::burn(&pool, "0xa", "0".into(), "1".into()),
+        Err(MpError::ZeroAmount {
+            operation: MpOperation::Burn,
+        })
+    );
+    assert_eq!(
+        PriceImpact::swap(&pool, "0xa", "0xa", "0".into(), "1".into()),
+        Err(MpError::ZeroAmount {
+            operation: MpOperation::Mint,
+        })
+    );
+}
+
+#[test]
+fn actions_report_price_impact() {
+    let settings = adapter()
+        .configure()
+        .amount_in("20")
+        .pool("0x123")
+        .asset_in("0xa")
+        .receiver("0xME")
+        .fetch()
+        .unwrap()
+        .mint()
+        .unwrap();
+    let impact = settings.price_impact.unwrap();
+    assert_eq!(impact.mid_price, "10".into());
+    assert!(impact.effective_price > impact.mid_price);
+    assert!(impact.impact_bps > SNum::ZERO);
+}
@@ -0,0 +1,115 @@
+use crate::{
+    multipool_math::{get_utilisable_mint_quantity, MpAsset, MpError, MpOperation},
+    num::{num::Num, snum::SNum},
+    tests::common::{self, pool},
+};
+use pretty_assertions::assert_eq;
+
+fn asset(quantity: &str, price: &str, percent: &str) -> MpAsset {
+    MpAsset {
+        percent: percent.into(),
+        ..common::asset(quantity, price)
+    }
+}
+
+#[test]
+fn added_asset_updates_totals() {
+    let mut pool = pool();
+    pool.add_asset("0xc", asset("10", "5", "25")).unwrap();
+    assert_eq!(pool.context.total_asset_percents, "125".into());
+    assert_eq!(pool.context.total_current_usd_amount, "1050".into());
+    assert!(pool.mint("0xc", "1".into()).is_ok());
+    assert!(matches!(
+        pool.add_asset("0xc", asset("0", "5", "25")),
+        Err(MpError::AssetAlreadyListed {
+            operation: MpOperation::Reconfigure,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn removed_asset_can_only_be_burned() {
+    let mut pool = pool();
+    pool.remove_asset("0xb").unwrap();
+    assert_eq!(pool.context.total_asset_percents, "50".into());
+    assert_eq!(pool.assets["0xb"].percent, Num::ZERO);
+    assert!(matches!(
+        pool.mint("0xb", "1".into()),
+        Err(MpError::ZeroPercent {
+            operation: MpOperation::Mint
+        })
+    ));
+    // burning the sunset asset moves the pool towards its new weights
+    assert!(pool.burn("0xb", "1".into()).is_ok());
+
+    let empty = asset("0", "1", "10");
+    pool.add_asset("0xc", empty).unwrap();
+    pool.remove_asset("0xc").unwrap();
+    assert!(!pool.assets.contains_key("0xc"));
+    assert_eq!(pool.context.total_asset_percents, "50".into());
+}
+
+#[test]
+fn zero_percent_is_not_mintable() {
+    let pool = pool();
+    let mut asset = pool.assets["0xa"].clone();
+    asset.percent = Num::ZERO;
+    let quantity =
+        get_utilisable_mint_quantity::<Num>(SNum::from("1"), &asset.sign(), &pool.context.sign());
+    assert_eq!(quantity, SNum::from("0"));
+}
+
+#[test]
+fn only_weighted_asset_is_still_mintable() {
+    let mut pool = pool();
+    pool.remove_asset("0xb").unwrap();
+    assert!(pool.mint("0xa", "1".into()).unwrap() > Num::ZERO);
+}
+
+#[test]
+fn last_weighted_asset_is_not_removable() {
+    let mut pool = pool();
+    pool.remove_asset("0xb").unwrap();
+    assert_eq!(
+        pool.remove_asset("0xa"),
+        Err(MpError::EmptyBasket {
+            operation: MpOperation::Reconfigure
+        })
+    );
+    assert_eq!(pool.context.total_asset_percents, "50".into());
+    assert!(pool.burn("0xb", "1".into()).is_ok());
+}
+
+#[test]
+fn migration_moves_towards_new_weights() {
+    let pool = pool();
+    let plan = pool
+        .plan_migration(
+            &[
+                ("0xa", "40".into()),
+                ("0xb", "40".into()),
+                ("0xc", "20".into()),
+            ],
+            &[("0xc", "2".into())],
+            "500".into(),
+        )
+        .unwrap();
+    assert_eq!(plan.pool.context.total_asset_percents, "100".into());
+    assert!(!plan.steps.is_empty());
+    assert!(plan.deviation_after < plan.deviation_before);
+    assert!(plan.pool.assets["0xc"].quantity > Num::ZERO);
+    // the original pool is left untouched
+    assert!(!pool.assets.contains_key("0xc"));
+}
+
+#[test]
+fn migration_of_unknown_asset_fails() {
+    assert!(matches!(
+        pool().plan_migration(&[("0xc", "20".into())], &[], "100".into()),
+        Err(MpError::UnknownAsset {
+            operation: MpOperation::Reconfigure,
+            ..
+        })
+    ));
+}
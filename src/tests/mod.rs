@@ -1,2 +1,23 @@
 pub mod actions;
+pub mod approval;
+pub mod async_actions;
+pub mod basket;
+pub mod builder;
+pub mod common;
+pub mod governance;
+pub mod health;
+pub mod impact;
+pub mod listing;
 pub mod multipool_math;
+pub mod numeric;
+pub mod payout;
+pub mod pool;
+pub mod quote;
+pub mod rebalance;
+pub mod routing;
+pub mod sampling;
+pub mod schedule;
+pub mod selector;
+pub mod slippage;
+pub mod solver;
+pub mod valuation;
@@ -1,6 +1,7 @@
 use crate::{
-    multipool_math::{MpAsset, MpContext, MpError},
+    multipool_math::{MpAsset, MpContext, MpError, MpOperation},
     num::num::Num,
+    tests::common::{asset, context},
 };
 use pretty_assertions::assert_eq;
 
@@ -908,7 +909,13 @@ fn burn_too_much() {
     let quantity_in = Num::from("50");
 
     let quantity_out = context.burn(&mut asset, quantity_in);
-    assert_eq!(Err(MpError::DeviationBiggerThanLimit), quantity_out)
+    assert!(matches!(
+        quantity_out,
+        Err(MpError::DeviationBiggerThanLimit {
+            operation: MpOperation::Burn,
+            ..
+        })
+    ))
 }
 
 #[test]
@@ -932,7 +939,13 @@ fn burn_too_much_reversed() {
     let quantity_in = Num::from("50");
 
     let quantity_out = context.burn_rev(&mut asset, quantity_in);
-    assert_eq!(Err(MpError::NoCurveSolutions), quantity_out)
+    assert!(matches!(
+        quantity_out,
+        Err(MpError::NoCurveSolutions {
+            operation: MpOperation::BurnRev,
+            ..
+        })
+    ))
 }
 
 #[test]
@@ -956,7 +969,13 @@ fn mint_too_much_being_bigger_than_limit() {
     let quantity_in = Num::from("5000");
 
     let quantity_out = context.mint(&mut asset, quantity_in);
-    assert_eq!(Err(MpError::DeviationBiggerThanLimit), quantity_out)
+    assert!(matches!(
+        quantity_out,
+        Err(MpError::DeviationBiggerThanLimit {
+            operation: MpOperation::Mint,
+            ..
+        })
+    ))
 }
 
 #[test]
@@ -980,7 +999,13 @@ fn mint_too_much_being_bigger_than_limit_reversed() {
     let quantity_in = Num::from("5000");
 
     let quantity_out = context.mint_rev(&mut asset, quantity_in);
-    assert_eq!(Err(MpError::DeviationBiggerThanLimit), quantity_out)
+    assert!(matches!(
+        quantity_out,
+        Err(MpError::DeviationBiggerThanLimit {
+            operation: MpOperation::MintRev,
+            ..
+        })
+    ))
 }
 
 #[test]
@@ -1004,7 +1029,13 @@ fn burn_too_much_being_bigger_than_limit_more_than_quantity() {
     let quantity_in = Num::from("5000");
 
     let quantity_out = context.burn(&mut asset, quantity_in);
-    assert_eq!(Err(MpError::InsufficientBurnQuantity), quantity_out)
+    assert!(matches!(
+        quantity_out,
+        Err(MpError::InsufficientBurnQuantity {
+            operation: MpOperation::Burn,
+            ..
+        })
+    ))
 }
 
 #[test]
@@ -1028,7 +1059,13 @@ fn burn_too_much_being_bigger_than_limit() {
     let quantity_in = Num::from("10");
 
     let quantity_out = context.burn(&mut asset, quantity_in);
-    assert_eq!(Err(MpError::DeviationBiggerThanLimit), quantity_out)
+    assert!(matches!(
+        quantity_out,
+        Err(MpError::DeviationBiggerThanLimit {
+            operation: MpOperation::Burn,
+            ..
+        })
+    ))
 }
 
 #[test]
@@ -1052,7 +1089,13 @@ fn burn_too_much_being_bigger_than_limit_more_than_quantity_reversed() {
     let quantity_in = Num::from("5000");
 
     let quantity_out = context.burn_rev(&mut asset, quantity_in);
-    assert_eq!(Err(MpError::InsufficientBurnQuantity), quantity_out)
+    assert!(matches!(
+        quantity_out,
+        Err(MpError::InsufficientBurnQuantity {
+            operation: MpOperation::BurnRev,
+            ..
+        })
+    ))
 }
 
 #[test]
@@ -1076,5 +1119,131 @@ fn burn_too_much_being_bigger_than_limit_reversed() {
     let quantity_in = Num::from("10");
 
     let quantity_out = context.burn_rev(&mut asset, quantity_in);
-    assert_eq!(Err(MpError::DeviationBiggerThanLimit), quantity_out)
+    assert!(matches!(
+        quantity_out,
+        Err(MpError::DeviationBiggerThanLimit {
+            operation: MpOperation::BurnRev,
+            ..
+        })
+    ))
+}
+
+#[test]
+fn mint_with_zero_price() {
+    let mut context = MpContext {
+        total_current_usd_amount: "1000".into(),
+        total_asset_percents: "100".into(),
+        curve_coef: "0.0003".into(),
+        deviation_percent_limit: "0.1".into(),
+        operation_base_fee: "0.0001".into(),
+        user_cashback_balance: "0".into(),
+    };
+    let mut asset = MpAsset {
+        quantity: "50".into(),
+        price: "0".into(),
+        collected_fees: "0".into(),
+        collected_cashbacks: "0".into(),
+        percent: "50".into(),
+    };
+
+    let quantity_out = context.mint(&mut asset, "5".into());
+    assert_eq!(
+        Err(MpError::ZeroPrice {
+            operation: MpOperation::Mint
+        }),
+        quantity_out
+    )
+}
+
+#[test]
+fn mint_with_zero_percent() {
+    let mut context = MpContext {
+        total_current_usd_amount: "1000".into(),
+        total_asset_percents: "100".into(),
+        curve_coef: "0.0003".into(),
+        deviation_percent_limit: "0.1".into(),
+        operation_base_fee: "0.0001".into(),
+        user_cashback_balance: "0".into(),
+    };
+    let mut asset = MpAsset {
+        quantity: "50".into(),
+        price: "10".into(),
+        collected_fees: "0".into(),
+        collected_cashbacks: "0".into(),
+        percent: "0".into(),
+    };
+
+    let quantity_out = context.mint_rev(&mut asset, "5".into());
+    assert_eq!(
+        Err(MpError::ZeroPercent {
+            operation: MpOperation::MintRev
+        }),
+        quantity_out
+    )
+}
+
+#[test]
+fn mint_with_overflow() {
+    let mut context = MpContext {
+        total_current_usd_amount: "1000".into(),
+        total_asset_percents: "100".into(),
+        curve_coef: "0.0003".into(),
+        deviation_percent_limit: "0.1".into(),
+        operation_base_fee: "0.0001".into(),
+        user_cashback_balance: Num::MAX,
+    };
+    let mut asset = MpAsset {
+        quantity: "20".into(),
+        price: "10".into(),
+        collected_fees: "0".into(),
+        collected_cashbacks: "10".into(),
+        percent: "50".into(),
+    };
+
+    let (context_before, asset_before) = (context.clone(), asset.clone());
+
+    let quantity_out = context.mint(&mut asset, "5.0005".into());
+    assert_eq!(
+        Err(MpError::Overflow {
+            operation: MpOperation::Mint
+        }),
+        quantity_out
+    );
+    assert_eq!(context, context_before);
+    assert_eq!(asset, asset_before);
+}
+
+#[test]
+fn error_display() {
+    let error = MpError::DeviationBiggerThanLimit {
+        operation: MpOperation::Burn,
+        deviation: "0.15".into(),
+        limit: "0.1".into(),
+    };
+    assert_eq!(
+        error.to_string(),
+        "burn: deviation 0.15 is bigger than limit 0.1"
+    );
+    let error = MpError::InsufficientBurnQuantity {
+        operation: MpOperation::BurnRev,
+        requested: "5000".into(),
+        available: "20".into(),
+    };
+    assert_eq!(
+        error.to_string(),
+        "reversed burn: requested 5000 while only 20 is available"
+    );
+}
+
+#[test]
+fn mint_of_huge_quantity_overflows() {
+    let mut context = context();
+    let mut asset = asset("50", "10");
+    let result = context.mint(&mut asset, "100000000000000".into());
+    assert_eq!(
+        result,
+        Err(MpError::Overflow {
+            operation: MpOperation::Mint
+        })
+    );
 }
@@ -0,0 +1,81 @@
+use crate::{
+    multipool_math::calculate_deviation_mint,
+    num::{num::Num, numeric::PoolNumeric},
+    tests::common::{asset, context},
+};
+use num_rational::BigRational;
+use num_traits::ToPrimitive;
+
+/// Runs the same sequence of operations on the given backend and reports
+/// every intermediate result as f64.
+fn operations<N: PoolNumeric>(to_f64: fn(&N) -> f64) -> Vec<f64> {
+    let mut context = context().to_numeric::<N>();
+    let mut asset = asset("50", "10").to_numeric::<N>();
+    let mut results = Vec::new();
+    for quantity in ["5.0051875", "20", "0.000001"] {
+        let out = context.mint(&mut asset, N::from_decimal(quantity)).unwrap();
+        results.push(to_f64(&out));
+    }
+    let out = context.burn(&mut asset, N::from_decimal("12")).unwrap();
+    results.push(to_f64(&out));
+    let out = context.mint_rev(&mut asset, N::from_decimal("3")).unwrap();
+    results.push(to_f64(&out));
+    let out = context.burn_rev(&mut asset, N::from_decimal("7")).unwrap();
+    results.push(to_f64(&out));
+    results.extend(
+        [
+            context.total_current_usd_amount,
+            context.user_cashback_balance,
+            asset.quantity,
+            asset.collected_fees,
+            asset.collected_cashbacks,
+        ]
+        .iter()
+        .map(to_f64),
+    );
+    results
+}
+
+fn assert_close(left: &[f64], right: &[f64]) {
+    assert_eq!(left.len(), right.len());
+    for (l, r) in left.iter().zip(right) {
+        assert!(
+            (l - r).abs() <= 1e-9 * l.abs().max(1.0),
+            "{left:?} != {right:?}"
+        );
+    }
+}
+
+#[test]
+fn backends_agree_on_operations() {
+    let fixed = operations::<Num>(|v| v.to_string().parse().unwrap());
+    let float = operations::<f64>(|v| *v);
+    let rational = operations::<BigRational>(|v| v.to_f64().unwrap());
+    assert_close(&fixed, &float);
+    assert_close(&fixed, &rational);
+}
+
+#[test]
+fn backends_agree_on_deviation() {
+    for quantity in ["0", "5", "25", "120"] {
+        let fixed = calculate_deviation_mint(quantity.into(), &asset("50", "10"), &context());
+        let float = calculate_deviation_mint(
+            PoolNumeric::from_decimal(quantity),
+            &asset("50", "10").to_numeric(),
+            &context().to_numeric(),
+        );
+        let fixed: f64 = fixed.to_string().parse().unwrap();
+        assert_close(&[fixed], &[float]);
+    }
+}
+
+#[test]
+fn rational_deviation_is_exact() {
+    let deviation = calculate_deviation_mint::<BigRational>(
+        PoolNumeric::from_decimal("5"),
+        &asset("50", "10").to_numeric(),
+        &context().to_numeric(),
+    );
+    // 550 / 1050 - 1 / 2
+    assert_eq!(deviation, BigRational::new(1.into(), 42.into()));
+}
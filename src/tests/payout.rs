@@ -0,0 +1,63 @@
+use crate::{multipool_math::MpError, num::num::Num, tests::common::skewed_pool};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn cashback_is_attributed_to_trader() {
+    let mut pool = skewed_pool("1");
+    pool.trade_as("alice", |pool| pool.mint("0xa", "2".into()))
+        .unwrap();
+    pool.trade_as("bob", |pool| pool.mint("0xb", "1".into()))
+        .unwrap();
+    let earned = pool.cashback_of("alice");
+    assert!(earned.quantities["0xa"] > Num::ZERO);
+    assert_eq!(earned.usd_value, earned.quantities["0xa"] * "10".into());
+    assert_eq!(pool.context.user_cashback_balance, earned.quantities["0xa"]);
+    // minting the overweight asset pays fees, not cashback
+    assert_eq!(pool.cashback_of("bob"), Default::default());
+}
+
+#[test]
+fn claim_pays_out_user_balance() {
+    let mut pool = skewed_pool("1");
+    pool.trade_as("alice", |pool| pool.mint("0xa", "2".into()))
+        .unwrap();
+    let total = pool.context.total_current_usd_amount;
+    let earned = pool.cashback_of("alice");
+    let claimed = pool.claim_cashback("alice").unwrap();
+    assert_eq!(claimed, earned.quantities);
+    assert!(claimed["0xa"] > Num::ZERO);
+    assert_eq!(pool.context.user_cashback_balance, Num::ZERO);
+    assert_eq!(pool.context.total_current_usd_amount, total);
+    assert_eq!(pool.claim_cashback("alice").unwrap(), Default::default());
+}
+
+#[test]
+fn failed_trade_earns_nothing() {
+    let mut pool = skewed_pool("1");
+    let before = pool.clone();
+    assert!(matches!(
+        pool.trade_as("alice", |pool| pool.mint("0xc", "1".into())),
+        Err(MpError::UnknownAsset { .. })
+    ));
+    assert_eq!(pool, before);
+}
+
+#[test]
+fn fee_withdrawal_empties_collected_fees() {
+    let mut pool = skewed_pool("1");
+    pool.mint("0xb", "1".into()).unwrap();
+    pool.mint("0xa", "1".into()).unwrap();
+    let total = pool.context.total_current_usd_amount;
+    let fees = pool.fees_since(&skewed_pool("1")).0;
+    let payout = pool.withdraw_fees();
+    assert_eq!(payout.quantities.len(), 2);
+    assert!(payout.usd_value > Num::ZERO && payout.usd_value <= fees);
+    assert!(pool
+        .assets
+        .values()
+        .all(|asset| asset.collected_fees.is_zero()));
+    assert_eq!(pool.context.total_current_usd_amount, total);
+    assert_eq!(pool.withdraw_fees(), Default::default());
+    // withdrawn fees no longer count towards the fees since
+    assert!(pool.fees_since(&skewed_pool("1")).0 < fees);
+}
@@ -0,0 +1,98 @@
+use crate::{
+    multipool_math::{
+        pool::{shares_for_value, value_for_shares, MpPool, Rounding},
+        MpError, MpOperation,
+    },
+    num::num::Num,
+    tests::common::{asset, context},
+};
+use pretty_assertions::assert_eq;
+
+fn pool() -> MpPool {
+    MpPool::new(context(), "100".into()).with_asset("0x345", asset("50", "10"))
+}
+
+#[test]
+fn first_deposit_issues_shares_per_usd() {
+    let mut pool = pool();
+    pool.context.total_current_usd_amount = Num::ZERO;
+    pool.total_supply = Num::ZERO;
+    pool.assets.get_mut("0x345").unwrap().quantity = Num::ZERO;
+
+    let shares = pool.mint("0x345", "10000000".into()).unwrap();
+    assert_eq!(shares, "100000000".into());
+    assert_eq!(pool.total_supply, "100000000".into());
+    assert_eq!(pool.context.total_current_usd_amount, "100000000".into());
+}
+
+#[test]
+fn mint_prices_shares_before_trade() {
+    let mut pool = pool();
+    let shares = pool.mint("0x345", "5.0051875".into()).unwrap();
+    // 5 utilisable at price 10 is 5% of the pool value
+    assert_eq!(shares, "5".into());
+    assert_eq!(pool.total_supply, "105".into());
+    assert_eq!(pool.context.total_current_usd_amount, "1050".into());
+}
+
+#[test]
+fn mint_rev_is_inverse_of_mint() {
+    let mut pool = pool();
+    let supplied = pool.mint_rev("0x345", "5".into()).unwrap();
+    let expected = Num::from("5.0051875");
+    assert!(supplied.max(expected) - supplied.min(expected) < "0.000000000001".into());
+    assert_eq!(pool.total_supply, "105".into());
+}
+
+#[test]
+fn burn_consumes_shares() {
+    let mut pool = pool();
+    let out = pool.burn("0x345", "5".into()).unwrap();
+    assert!(out < "5".into() && out > "4.9".into());
+    assert_eq!(pool.total_supply, "95".into());
+    assert_eq!(pool.assets["0x345"].quantity, "45".into());
+
+    let mut reversed = self::pool();
+    let shares = reversed.burn_rev("0x345", out).unwrap();
+    assert!(shares >= "4.999999999".into() && shares <= "5.000000001".into());
+    assert_eq!(reversed.total_supply, Num::from("100") - shares);
+}
+
+#[test]
+fn burn_checks_share_supply() {
+    let mut pool = pool();
+    let result = pool.burn("0x345", "101".into());
+    assert!(matches!(
+        result,
+        Err(MpError::InsufficientShares {
+            operation: MpOperation::Burn,
+            ..
+        })
+    ));
+    let result = pool.mint("0x999", "1".into());
+    assert!(matches!(result, Err(MpError::UnknownAsset { .. })));
+    assert_eq!(pool, self::pool());
+}
+
+#[test]
+fn share_conversions_round_in_favour_of_pool() {
+    let (value, total, supply) = (Num::from("1"), Num::from("3"), Num::from("1"));
+    assert_eq!(
+        shares_for_value(value, total, supply, Rounding::Down)
+            .unwrap()
+            .to_string(),
+        "0.333333333333333333333333"
+    );
+    assert_eq!(
+        shares_for_value(value, total, supply, Rounding::Up)
+            .unwrap()
+            .to_string(),
+        "0.333333333333333333333334"
+    );
+    assert_eq!(
+        value_for_shares("2".into(), "10".into(), "3".into(), Rounding::Down)
+            .unwrap()
+            .to_string(),
+        "6.666666666666666666666666"
+    );
+}
@@ -0,0 +1,190 @@
+use crate::{
+    multipool_math::actions::{
+        adapter::{MockedAdapter, MpAdapter},
+        error::ActionError,
+        quote::{Quote, QuoteOperation, TxnResult},
+        settings::ActionSettings,
+        DeadlineKind,
+    },
+    tests::common::adapter,
+};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn mint_quote_describes_the_trade() {
+    let quote = adapter()
+        .configure()
+        .amount_in("5")
+        .pool("0x123")
+        .asset_in("0xa")
+        .receiver("0xME")
+        .blocks_to_live("10")
+        .fetch()
+        .unwrap()
+        .mint()
+        .unwrap()
+        .quote()
+        .unwrap();
+    assert_eq!(quote.operation, QuoteOperation::Mint);
+    assert_eq!(quote.asset_out_address, None);
+    assert_eq!(quote.amount_in, "5".into());
+    assert_eq!(quote.amount_out, quote.shares);
+    assert_eq!(quote.before.total_supply, "100".into());
+    assert_eq!(
+        quote.after.total_supply,
+        quote.before.total_supply + quote.shares
+    );
+    assert!(quote.fees.fee > "0".into());
+    assert_eq!(quote.block, "100".into());
+    assert_eq!(quote.deadline, "110".into());
+    assert_eq!(quote.deadline_kind, DeadlineKind::Block);
+}
+
+#[test]
+fn quote_round_trips_through_json_and_sends() {
+    let mut settings = adapter()
+        .configure()
+        .amount_in("2")
+        .pool("0x123")
+        .asset_in("0xa")
+        .asset_out("0xb")
+        .receiver("0xME")
+        .slippage_percent("0.01")
+        .fetch()
+        .unwrap()
+        .swap()
+        .unwrap();
+    let json = settings.quote().unwrap().to_json().unwrap();
+    let quote = Quote::from_json(&json).unwrap();
+    assert_eq!(quote, settings.quote().unwrap());
+
+    let expected = settings.send_swap().unwrap();
+    let sent = adapter().configure().send(&quote).unwrap();
+    let TxnResult::Swap(params) = sent else {
+        panic!("swap quote sent as {sent:?}");
+    };
+    assert_eq!(params.shares, expected.shares);
+    assert_eq!(params.amount_out_min, expected.amount_out_min);
+    assert_eq!(params.amount_in_max, expected.amount_in_max);
+    assert!(quote.amount_out_min < quote.amount_out);
+}
+
+#[test]
+fn quote_requires_a_calculation() {
+    let settings = adapter()
+        .configure()
+        .pool("0x123")
+        .asset_out("0xb")
+        .fetch()
+        .unwrap();
+    assert_eq!(
+        settings.quote().unwrap_err(),
+        ActionError::MissingField("quote")
+    );
+}
+
+fn quoted_swap() -> ActionSettings<MockedAdapter> {
+    adapter()
+        .configure()
+        .amount_in("2")
+        .pool("0x123")
+        .asset_in("0xa")
+        .asset_out("0xb")
+        .receiver("0xME")
+        .slippage_percent("0.01")
+        .blocks_to_live("10")
+        .fetch()
+        .unwrap()
+        .swap()
+        .unwrap()
+}
+
+#[test]
+fn revalidation_keeps_bounds_of_an_unchanged_quote() {
+    let settings = quoted_swap();
+    let quote = settings.quote().unwrap();
+    let revalidated = settings.revalidate().unwrap().quote().unwrap();
+    assert_eq!(revalidated, quote);
+}
+
+#[test]
+fn revalidation_rejects_moved_state() {
+    let mut settings = quoted_swap();
+    let quote = settings.quote().unwrap();
+    let adapter = settings.adapter.as_mut().unwrap();
+    adapter.assets.get_mut("0xb").unwrap().price = "21".into();
+    let ActionError::SlippageExceeded { quoted, bound } = settings.revalidate().unwrap_err() else {
+        panic!("expected a slippage error");
+    };
+    assert!(quoted < bound);
+    assert_eq!(bound, quote.amount_out_min);
+}
+
+#[test]
+fn revalidation_rejects_expired_quotes() {
+    let mut settings = quoted_swap();
+    settings.adapter.as_mut().unwrap().current_block = "111".into();
+    assert_eq!(
+        settings.revalidate().unwrap_err(),
+        ActionError::DeadlineExpired {
+            deadline: "110".into(),
+            now: "111".into(),
+        }
+    );
+}
+
+#[test]
+fn revalidation_refreshes_amounts_within_bounds() {
+    let mut settings = quoted_swap();
+    let quote = settings.quote().unwrap();
+    let adapter = settings.adapter.as_mut().unwrap();
+    adapter.assets.get_mut("0xb").unwrap().price = "19.9".into();
+    adapter.current_block = "110".into();
+    let revalidated = settings.revalidate().unwrap();
+    let fresh = revalidated.quote().unwrap();
+    assert!(fresh.amount_out > quote.amount_out);
+    assert_eq!(fresh.amount_out_min, quote.amount_out_min);
+    assert_eq!(fresh.block, "110".into());
+    let params = revalidated.swap_params.unwrap();
+    assert_eq!(params.amount_out_min, quote.amount_out_min);
+    assert_eq!(params.shares, fresh.shares);
+}
+
+#[test]
+fn revalidation_tolerates_adverse_moves_within_slippage() {
+    let mut mint = adapter()
+        .configure()
+        .amount_in("5")
+        .pool("0x123")
+        .asset_in("0xa")
+        .receiver("0xME")
+        .slippage_percent("0.01")
+        .fetch()
+        .unwrap()
+        .mint()
+        .unwrap();
+    let quote = mint.quote().unwrap();
+    let assets = &mut mint.adapter.as_mut().unwrap().assets;
+    assets.get_mut("0xa").unwrap().price = "9.95".into();
+    let fresh = mint.revalidate().unwrap().quote().unwrap();
+    assert!(fresh.shares < quote.shares);
+    assert_eq!(fresh.amount_out_min, quote.amount_out_min);
+
+    let mut burn = adapter()
+        .configure()
+        .amount_out("1")
+        .pool("0x123")
+        .asset_out("0xb")
+        .receiver("0xME")
+        .slippage_percent("0.01")
+        .fetch()
+        .unwrap()
+        .burn()
+        .unwrap();
+    let quote = burn.quote().unwrap();
+    let assets = &mut burn.adapter.as_mut().unwrap().assets;
+    assets.get_mut("0xb").unwrap().price = "20.1".into();
+    let fresh = burn.revalidate().unwrap().quote().unwrap();
+    assert!(fresh.shares > quote.shares);
+    assert_eq!(fresh.amount_in_max, quote.amount_in_max);
+}
@@ -0,0 +1,56 @@
+use crate::{
+    multipool_math::{calculate_deviation_mint, rebalance::RebalanceAction},
+    num::num::Num,
+    tests::common::{pool, skewed_pool},
+};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn swap_moves_value_between_assets() {
+    let mut pool = pool();
+    let (shares, quantity_out) = pool.swap("0xa", "0xb", "2".into()).unwrap();
+    assert!(shares < "2".into());
+    assert!(quantity_out < "1".into() && quantity_out > "0.99".into());
+    assert_eq!(pool.total_supply, "100".into());
+    let quantity_a = pool.assets["0xa"].quantity;
+    assert!(quantity_a < "52".into() && quantity_a > "51.99".into());
+}
+
+#[test]
+fn plan_moves_drifted_pool_to_targets() {
+    let pool = skewed_pool("1");
+    let plan = pool.plan_rebalance("100".into());
+    assert!(!plan.steps.is_empty());
+    assert!(plan.spent <= "100".into());
+    assert!(plan.deviation_after < plan.deviation_before / Num::from("10"));
+    assert!(!plan.cashback.is_zero());
+    // swapping underweight in and overweight out fixes both sides at once
+    assert!(matches!(
+        &plan.steps[0].action,
+        RebalanceAction::Swap { asset_in, asset_out, .. } if asset_in == "0xa" && asset_out == "0xb"
+    ));
+    let mut deviation = plan.deviation_before;
+    for step in &plan.steps {
+        assert!(step.deviation < deviation);
+        deviation = step.deviation;
+    }
+    for asset in plan.pool.assets.values() {
+        assert!(calculate_deviation_mint(Num::ZERO, asset, &plan.pool.context) < "0.01".into());
+    }
+}
+
+#[test]
+fn plan_respects_budget() {
+    let pool = skewed_pool("1");
+    let plan = pool.plan_rebalance("10".into());
+    assert!(plan.spent <= "10".into());
+    assert!(plan.deviation_after < plan.deviation_before);
+    assert_eq!(pool.plan_rebalance(Num::ZERO).steps, []);
+}
+
+#[test]
+fn balanced_pool_needs_no_trades() {
+    let plan = pool().plan_rebalance("100".into());
+    assert_eq!(plan.steps, []);
+    assert_eq!(plan.deviation_after, Num::ZERO);
+}
@@ -0,0 +1,318 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    multipool_math::{
+        actions::{
+            adapter::MpAdapter,
+            error::ActionError,
+            quote::QuoteOperation,
+            routing::Router,
+            settings::{BurnTxnParams, MintTxnParams, SwapTxnParams},
+            DeadlineKind,
+        },
+        pool::MpPool,
+        MpAsset, MpContext, MpError,
+    },
+    num::num::Num,
+};
+use pretty_assertions::assert_eq;
+
+/// Adapter serving several pools by address.
+struct Pools(BTreeMap<String, MpPool>);
+
+impl Pools {
+    fn pool(&self, address: &str) -> Result<&MpPool, String> {
+        self.0
+            .get(address)
+            .ok_or_else(|| format!("no such pool: {address}"))
+    }
+}
+
+impl MpAdapter for Pools {
+    type Error = String;
+    type MintTxnResult = ();
+    type BurnTxnResult = ();
+    type SwapTxnResult = ();
+
+    fn get_context(&mut self, address: &str) -> Result<MpContext, Self::Error> {
+        Ok(self.pool(address)?.context.clone())
+    }
+    fn get_total_supply(&mut self, address: &str) -> Result<Num, Self::Error> {
+        Ok(self.pool(address)?.total_supply)
+    }
+    fn get_asset(&mut self, address: &str, asset: &str) -> Result<MpAsset, Self::Error> {
+        self.pool(address)?
+            .assets
+            .get(asset)
+            .cloned()
+            .ok_or_else(|| format!("{asset} is not listed in {address}"))
+    }
+    fn get_current_block(&mut self) -> Result<Num, Self::Error> {
+        Ok("100".into())
+    }
+    fn get_current_timestamp(&mut self) -> Result<Num, Self::Error> {
+        Ok("1700000000".into())
+    }
+    fn transact_mint(&mut self, _params: &MintTxnParams) {}
+    fn transact_burn(&mut self, _params: &BurnTxnParams) {}
+    fn transact_swap(&mut self, _params: &SwapTxnParams) {}
+}
+
+/// Pool at its target weights, every asset having an equal share.
+fn pool(base_fee: &str, assets: &[(&str, &str, &str)]) -> MpPool {
+    let mut total = Num::ZERO;
+    let mut pool = MpPool::new(
+        MpContext {
+            total_current_usd_amount: Num::ZERO,
+            total_asset_percents: "100".into(),
+            curve_coef: "0.0003".into(),
+            deviation_percent_limit: "0.1".into(),
+            operation_base_fee: base_fee.into(),
+            user_cashback_balance: "0".into(),
+        },
+        "100".into(),
+    );
+    let percent = Num::from("100") / Num::with_decimals((assets.len() as u64).into(), 0);
+    for (address, quantity, price) in assets {
+        let asset = MpAsset {
+            quantity: (*quantity).into(),
+            price: (*price).into(),
+            collected_fees: "0".into(),
+            collected_cashbacks: "0".into(),
+            percent,
+        };
+        total += asset.quantity * asset.price;
+        pool = pool.with_asset(address, asset);
+    }
+    pool.context.total_current_usd_amount = total;
+    pool
+}
+
+fn router(pools: &[(&str, MpPool)]) -> Router<Pools> {
+    let adapter = Pools(
+        pools
+            .iter()
+            .map(|(address, pool)| (address.to_string(), pool.clone()))
+            .collect(),
+    );
+    pools
+        .iter()
+        .fold(Router::new(adapter), |router, (address, pool)| {
+            router.pool(*address, pool.assets.keys().cloned())
+        })
+        .sender("0xTRADER")
+        .receiver("0xME")
+}
+
+#[test]
+fn cheapest_direct_swap_is_chosen() {
+    let mut router = router(&[
+        (
+            "0xp1",
+            pool("0.01", &[("0xa", "50", "10"), ("0xb", "25", "20")]),
+        ),
+        (
+            "0xp2",
+            pool("0.0001", &[("0xa", "50", "10"), ("0xb", "25", "20")]),
+        ),
+    ]);
+    let route = router.best_route("0xa", "0xb", "2").unwrap();
+    assert_eq!(route.legs.len(), 1);
+    let leg = &route.legs[0];
+    assert_eq!(leg.operation, QuoteOperation::Swap);
+    assert_eq!(leg.pool_address, "0xp2");
+    assert_eq!(leg.receiver_address, "0xME");
+    assert_eq!(leg.block, "100".into());
+    assert_eq!(route.amount_out, leg.amount_out);
+    assert!(route.amount_out > "0.99".into() && route.amount_out < "1".into());
+}
+
+#[test]
+fn shared_asset_links_two_pools() {
+    let mut router = router(&[
+        (
+            "0xp1",
+            pool("0.0001", &[("0xa", "50", "10"), ("0xc", "100", "5")]),
+        ),
+        (
+            "0xp2",
+            pool("0.0001", &[("0xc", "100", "5"), ("0xb", "25", "20")]),
+        ),
+    ])
+    .slippage_percent("0.01")
+    .blocks_to_live("10");
+    let route = router.best_route("0xa", "0xb", "2").unwrap();
+    let hops: Vec<_> = route
+        .legs
+        .iter()
+        .map(|leg| (leg.pool_address.as_str(), leg.operation))
+        .collect();
+    assert_eq!(
+        hops,
+        [
+            ("0xp1", QuoteOperation::Swap),
+            ("0xp2", QuoteOperation::Swap)
+        ]
+    );
+    assert_eq!(route.legs[0].asset_out_address.as_deref(), Some("0xc"));
+    assert_eq!(route.legs[1].amount_in, route.legs[0].amount_out);
+    for leg in &route.legs {
+        assert_eq!(
+            leg.amount_out_min,
+            leg.amount_out - leg.amount_out * Num::from("0.005")
+        );
+        assert_eq!(leg.deadline, "110".into());
+        assert_eq!(leg.deadline_kind, DeadlineKind::Block);
+    }
+    let first = &route.legs[0];
+    assert_eq!(
+        first.amount_in_max,
+        first.amount_in + first.amount_in * Num::from("0.005")
+    );
+    assert_eq!(first.receiver_address, "0xTRADER");
+    // the second leg can't spend more than the first one pays out
+    assert_eq!(route.legs[1].amount_in_max, first.amount_out_min);
+    assert_eq!(route.legs[1].receiver_address, "0xME");
+    assert!(route.legs[1].amount_out_min > route.amount_out - route.amount_out * Num::from("0.01"));
+}
+
+#[test]
+fn share_tokens_route_through_another_pool() {
+    let mut router = router(&[
+        (
+            "0xp1",
+            pool("0.0001", &[("0xa", "50", "10"), ("0xc", "100", "5")]),
+        ),
+        (
+            "0xp2",
+            pool("0.0001", &[("0xp1", "50", "10"), ("0xb", "25", "20")]),
+        ),
+    ]);
+    let route = router.best_route("0xa", "0xb", "2").unwrap();
+    let operations: Vec<_> = route.legs.iter().map(|leg| leg.operation).collect();
+    assert_eq!(operations, [QuoteOperation::Mint, QuoteOperation::Swap]);
+    assert_eq!(route.legs[0].shares, route.legs[1].amount_in);
+    assert!(route.legs[1].mint_params().is_none());
+    assert_eq!(
+        route.legs[1].swap_params().unwrap().asset_in_address,
+        "0xp1"
+    );
+
+    let back = router.best_route("0xb", "0xa", "1").unwrap();
+    let operations: Vec<_> = back.legs.iter().map(|leg| leg.operation).collect();
+    assert_eq!(operations, [QuoteOperation::Swap, QuoteOperation::Burn]);
+}
+
+#[test]
+fn unconnected_assets_have_no_route() {
+    let mut router = router(&[
+        (
+            "0xp1",
+            pool("0.0001", &[("0xa", "50", "10"), ("0xc", "100", "5")]),
+        ),
+        (
+            "0xp2",
+            pool("0.0001", &[("0xd", "100", "5"), ("0xb", "25", "20")]),
+        ),
+    ]);
+    assert_eq!(
+        router.best_route("0xa", "0xb", "2").unwrap_err(),
+        ActionError::NoRoute {
+            asset_in: "0xa".into(),
+            asset_out: "0xb".into(),
+            cause: None,
+        }
+    );
+}
+
+#[test]
+fn failing_paths_report_their_error() {
+    let mut router = router(&[(
+        "0xp1",
+        pool("0.0001", &[("0xa", "50", "10"), ("0xb", "25", "20")]),
+    )]);
+    let error = router.best_route("0xa", "0xb", "100").unwrap_err();
+    assert!(
+        matches!(
+            error,
+            ActionError::NoRoute {
+                cause: Some(MpError::DeviationBiggerThanLimit { .. }),
+                ..
+            }
+        ),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn paths_require_a_sender() {
+    let mut router = router(&[
+        (
+            "0xp1",
+            pool("0.0001", &[("0xa", "50", "10"), ("0xc", "100", "5")]),
+        ),
+        (
+            "0xp2",
+            pool("0.0001", &[("0xc", "100", "5"), ("0xb", "25", "20")]),
+        ),
+    ]);
+    router.sender_address = None;
+    assert_eq!(
+        router.best_route("0xa", "0xb", "2").unwrap_err(),
+        ActionError::MissingField("sender address")
+    );
+}
+
+#[test]
+fn splitting_between_pools_raises_amount_out() {
+    let pair = || pool("0.0001", &[("0xa", "50", "10"), ("0xb", "25", "20")]);
+    let mut router = router(&[("0xp1", pair()), ("0xp2", pair())])
+        .slippage_percent("0.01")
+        .until_block("120");
+    let single = router.best_route("0xa", "0xb", "10").unwrap();
+    let split = router.split_route("0xa", "0xb", "10", 10).unwrap();
+    assert_eq!(split.legs.len(), 2);
+    assert!(split.amount_out > single.amount_out);
+    assert_eq!(
+        split.amount_out,
+        split.legs.iter().map(|leg| leg.amount_out).sum::<Num>()
+    );
+    for leg in &split.legs {
+        assert_eq!(leg.amount_in, "5".into());
+        let params = leg.swap_params().unwrap();
+        assert_eq!(params.receiver_address, "0xME");
+        assert_eq!(
+            params.amount_out_min,
+            leg.amount_out - leg.amount_out * Num::from("0.01")
+        );
+        assert_eq!(params.deadline, "120".into());
+    }
+}
+
+#[test]
+fn deeper_pools_take_larger_parts() {
+    let mut router = router(&[
+        (
+            "0xp1",
+            pool("0.0001", &[("0xa", "50", "10"), ("0xb", "25", "20")]),
+        ),
+        (
+            "0xp2",
+            pool("0.0001", &[("0xa", "150", "10"), ("0xb", "75", "20")]),
+        ),
+        (
+            "0xp3",
+            pool("0.0001", &[("0xa", "50", "10"), ("0xc", "100", "5")]),
+        ),
+    ]);
+    let split = router.split_route("0xa", "0xb", "12", 12).unwrap();
+    let parts: Vec<_> = split
+        .legs
+        .iter()
+        .map(|leg| (leg.pool_address.as_str(), leg.amount_in))
+        .collect();
+    assert_eq!(parts.len(), 2);
+    assert!(parts[1].1 > parts[0].1);
+    assert_eq!(parts[0].1 + parts[1].1, "12".into());
+    assert!(router.split_route("0xa", "0xd", "1", 4).is_err());
+}
@@ -0,0 +1,147 @@
+use crate::{
+    multipool_math::{
+        sampling::{linear_grid, sample_fee_curve, SweepParameter},
+        MpAsset, MpContext, MpOperation,
+    },
+    num::{num::Num, snum::SNum},
+    tests::common::{asset, context},
+};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn linear_grid_includes_bounds() {
+    assert_eq!(
+        linear_grid("1".into(), "2".into(), 5),
+        ["1", "1.25", "1.5", "1.75", "2"].map(Num::from)
+    );
+}
+
+#[test]
+fn linear_grid_descends() {
+    assert_eq!(
+        linear_grid("10".into(), "1".into(), 4),
+        ["10", "7", "4", "1"].map(Num::from)
+    );
+}
+
+#[test]
+fn mint_fee_grows_with_amount() {
+    let grid = linear_grid("1".into(), "20".into(), 20);
+    let curve = sample_fee_curve(
+        &context(),
+        &asset("50", "10"),
+        MpOperation::Mint,
+        SweepParameter::Amount,
+        Num::ZERO,
+        &grid,
+    );
+    assert_eq!(curve.samples.len(), 20);
+    let fees: Vec<Num> = curve.samples.iter().map(|s| s.fee.unwrap()).collect();
+    assert!(fees.windows(2).all(|pair| pair[0] < pair[1]));
+    // fees are convex in amount
+    let derivatives: Vec<SNum> = curve
+        .samples
+        .iter()
+        .map(|s| s.fee_derivative.unwrap())
+        .collect();
+    assert!(derivatives[0] > SNum::ZERO);
+    assert!(derivatives[19] > derivatives[0]);
+}
+
+#[test]
+fn burn_past_limit_has_no_sample() {
+    let grid = ["5", "25"].map(Num::from);
+    let curve = sample_fee_curve(
+        &context(),
+        &asset("50", "10"),
+        MpOperation::Burn,
+        SweepParameter::Amount,
+        Num::ZERO,
+        &grid,
+    );
+    assert!(curve.samples[0].fee.is_some());
+    assert_eq!(curve.samples[1].fee, None);
+    assert_eq!(curve.samples[0].fee_derivative, None);
+}
+
+#[test]
+fn fee_grows_with_curve_coef() {
+    let grid = linear_grid("0.0001".into(), "0.001".into(), 4);
+    let curve = sample_fee_curve(
+        &context(),
+        &asset("50", "10"),
+        MpOperation::Mint,
+        SweepParameter::CurveCoef,
+        "10".into(),
+        &grid,
+    );
+    assert!(curve
+        .samples
+        .iter()
+        .all(|s| s.fee_derivative.unwrap() > SNum::ZERO));
+}
+
+#[test]
+fn percent_beyond_total_has_no_sample() {
+    let context = MpContext {
+        total_asset_percents: "50".into(),
+        ..context()
+    };
+    let asset = MpAsset {
+        percent: "60".into(),
+        ..asset("50", "10")
+    };
+    let grid = ["10", "20"].map(Num::from);
+    let curve = sample_fee_curve(
+        &context,
+        &asset,
+        MpOperation::Mint,
+        SweepParameter::Percent,
+        "10".into(),
+        &grid,
+    );
+    assert!(curve.samples.iter().all(|s| s.fee.is_none()));
+}
+
+#[test]
+fn descending_grid_has_derivatives() {
+    let grid = linear_grid("20".into(), "1".into(), 5);
+    let curve = sample_fee_curve(
+        &context(),
+        &asset("50", "10"),
+        MpOperation::Mint,
+        SweepParameter::Amount,
+        Num::ZERO,
+        &grid,
+    );
+    assert!(curve
+        .samples
+        .iter()
+        .all(|s| s.fee_derivative.unwrap() > SNum::ZERO));
+}
+
+#[test]
+fn curve_exports_csv_and_json() {
+    let grid = ["1", "2"].map(Num::from);
+    let curve = sample_fee_curve(
+        &context(),
+        &asset("50", "10"),
+        MpOperation::Mint,
+        SweepParameter::Amount,
+        Num::ZERO,
+        &grid,
+    );
+    let csv = curve.to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(
+        lines[0],
+        "x,quantity_out,fee,cashback,deviation,fee_derivative"
+    );
+    assert!(lines[1].starts_with("1,"));
+
+    let json: serde_json::Value = serde_json::from_str(&curve.to_json().unwrap()).unwrap();
+    assert_eq!(json["operation"], "Mint");
+    assert_eq!(json["parameter"], "Amount");
+    assert_eq!(json["samples"][1]["x"], "2");
+}
@@ -0,0 +1,72 @@
+use crate::{
+    multipool_math::actions::{adapter::MpAdapter, SidedQuantity},
+    num::num::Num,
+    tests::common::adapter,
+};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn splitting_with_rebalancing_lowers_fees() {
+    let settings = adapter()
+        .configure()
+        .amount_in("40")
+        .pool("0x123")
+        .asset_in("0xa")
+        .fetch()
+        .unwrap();
+    let schedules = settings.schedules(&[1, 4, 8], "1".into()).unwrap();
+    assert_eq!(schedules.len(), 3);
+    for schedule in &schedules {
+        assert_eq!(schedule.amounts_in.len() as u32, schedule.chunks);
+        assert_eq!(schedule.total_in, "40".into());
+    }
+    assert!(schedules[1].total_fee < schedules[0].total_fee);
+    assert!(schedules[2].total_fee < schedules[1].total_fee);
+    assert!(schedules[2].total_out > schedules[0].total_out);
+}
+
+#[test]
+fn rebalancing_between_chunks_matters() {
+    let settings = adapter()
+        .configure()
+        .amount_in("40")
+        .pool("0x123")
+        .asset_in("0xa")
+        .fetch()
+        .unwrap();
+    let frozen = settings.schedules(&[4], Num::ZERO).unwrap();
+    let rebalanced = settings.schedules(&[4], "0.5".into()).unwrap();
+    assert!(rebalanced[0].total_fee < frozen[0].total_fee);
+    // a single chunk does not depend on the model
+    let frozen = settings.schedules(&[1], Num::ZERO).unwrap();
+    let rebalanced = settings.schedules(&[1], "0.5".into()).unwrap();
+    assert_eq!(frozen[0].total_fee, rebalanced[0].total_fee);
+}
+
+#[test]
+fn split_produces_chunk_settings() {
+    let settings = adapter()
+        .configure()
+        .amount_out("10")
+        .pool("0x123")
+        .asset_in("0xa")
+        .receiver("0xME")
+        .fetch()
+        .unwrap();
+    let chunks = settings.split(3).unwrap();
+    assert_eq!(chunks.len(), 3);
+    let total: Num = chunks
+        .iter()
+        .map(|chunk| match chunk.quantity {
+            Some(SidedQuantity::QuantityOut(shares)) => shares,
+            _ => panic!("quantity side changed"),
+        })
+        .sum();
+    assert_eq!(total, "10".into());
+    let Some(SidedQuantity::QuantityOut(chunk_shares)) = chunks[0].quantity else {
+        panic!("quantity side changed");
+    };
+    let params = chunks[0].clone().mint().unwrap().mint_params.unwrap();
+    assert_eq!(params.shares, chunk_shares);
+    assert!(params.amount_in_max > "1.1".into());
+}
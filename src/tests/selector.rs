@@ -0,0 +1,48 @@
+use crate::{
+    multipool_math::{pool::MpPool, MpAsset, MpError, MpOperation},
+    num::{num::Num, snum::SNum},
+    tests::common::{asset, skewed_pool},
+};
+use pretty_assertions::assert_eq;
+
+fn pool() -> MpPool {
+    // 0xa is under its target share, 0xb is over it, 0xc is delisted
+    let delisted = MpAsset {
+        percent: "0".into(),
+        collected_cashbacks: "1".into(),
+        ..asset("0", "5")
+    };
+    skewed_pool("1").with_asset("0xc", delisted)
+}
+
+#[test]
+fn best_mint_asset_prefers_underweight_asset() {
+    let ranks = pool().best_mint_asset("10".into());
+    let addresses: Vec<&str> = ranks.iter().map(|rank| rank.address.as_str()).collect();
+    assert_eq!(addresses, ["0xa", "0xb", "0xc"]);
+
+    let best = ranks[0].quote.as_ref().unwrap();
+    assert_eq!(best.leg.quantity, "1".into());
+    assert!(best.cost < SNum::ZERO);
+    assert!(best.deviation_after < best.deviation_before);
+    let second = ranks[1].quote.as_ref().unwrap();
+    assert!(second.cost > SNum::ZERO);
+    assert!(matches!(
+        ranks[2].quote,
+        Err(MpError::ZeroPercent {
+            operation: MpOperation::Mint
+        })
+    ));
+}
+
+#[test]
+fn best_burn_asset_prefers_overweight_asset() {
+    let pool = pool();
+    let ranks = pool.best_burn_asset("1".into());
+    assert_eq!(ranks[0].address, "0xb");
+    assert_eq!(ranks[1].address, "0xa");
+    assert!(ranks[0].quote.as_ref().unwrap().leg.cashback > Num::ZERO);
+    assert!(ranks[2].quote.is_err());
+    // ranking does not change the pool
+    assert_eq!(pool, self::pool());
+}
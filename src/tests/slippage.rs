@@ -0,0 +1,170 @@
+use crate::{
+    multipool_math::actions::{
+        adapter::{MockedAdapter, MpAdapter},
+        error::ActionError,
+        settings::ActionSettings,
+        Slippage,
+    },
+    num::num::Num,
+    tests::common::adapter,
+};
+use pretty_assertions::assert_eq;
+
+fn swap() -> ActionSettings<MockedAdapter> {
+    adapter()
+        .configure()
+        .amount_in("2")
+        .pool("0x123")
+        .asset_in("0xa")
+        .asset_out("0xb")
+        .receiver("0xME")
+        .fetch()
+        .unwrap()
+}
+
+fn burn() -> ActionSettings<MockedAdapter> {
+    adapter()
+        .configure()
+        .amount_in("1")
+        .pool("0x123")
+        .asset_out("0xb")
+        .receiver("0xME")
+        .fetch()
+        .unwrap()
+}
+
+#[test]
+fn relative_slippage_lowers_amount_out() {
+    let quoted = burn().burn().unwrap().burn_params.unwrap().amount_out_min;
+    let params = burn()
+        .slippage_percent("0.01")
+        .burn()
+        .unwrap()
+        .burn_params
+        .unwrap();
+    assert_eq!(params.amount_out_min, quoted - quoted * "0.01".into());
+
+    let quoted = swap().swap().unwrap().swap_params.unwrap().amount_out_min;
+    let params = swap()
+        .slippage_bps("50")
+        .swap()
+        .unwrap()
+        .swap_params
+        .unwrap();
+    assert_eq!(params.amount_out_min, quoted - quoted * "0.005".into());
+    assert_eq!(params.amount_in_max, Num::from("2") + Num::from("0.01"));
+}
+
+#[test]
+fn slippage_loosens_the_exact_side() {
+    let params = adapter()
+        .configure()
+        .amount_in("5")
+        .pool("0x123")
+        .asset_in("0xa")
+        .receiver("0xME")
+        .slippage_percent("0.01")
+        .fetch()
+        .unwrap()
+        .mint()
+        .unwrap()
+        .mint_params
+        .unwrap();
+    assert_eq!(params.amount_in_max, Num::from("5") + Num::from("0.05"));
+
+    let mut settings = adapter()
+        .configure()
+        .amount_out("1")
+        .pool("0x123")
+        .asset_out("0xb")
+        .receiver("0xME")
+        .slippage_percent("0.01")
+        .fetch()
+        .unwrap()
+        .burn()
+        .unwrap();
+    assert_eq!(
+        settings.burn_params.take().unwrap().amount_out_min,
+        "0.99".into()
+    );
+    assert!(settings.quote().unwrap().amount_in_max > settings.quote().unwrap().shares);
+
+    let error = adapter()
+        .configure()
+        .amount_out("1")
+        .pool("0x123")
+        .asset_out("0xb")
+        .receiver("0xME")
+        .min_amount_out("2")
+        .fetch()
+        .unwrap()
+        .burn()
+        .unwrap_err();
+    assert!(matches!(error, ActionError::SlippageExceeded { .. }));
+}
+
+#[test]
+fn relative_slippage_raises_amount_in() {
+    let mint = |slippage: Option<Slippage>| {
+        let mut settings = adapter()
+            .configure()
+            .amount_out("10")
+            .pool("0x123")
+            .asset_in("0xa")
+            .receiver("0xME")
+            .fetch()
+            .unwrap();
+        settings.slippage = slippage;
+        settings.mint().unwrap().mint_params.unwrap().amount_in_max
+    };
+    let quoted = mint(None);
+    assert_eq!(
+        mint(Some(Slippage::Percent("0.1".into()))),
+        quoted + quoted * "0.1".into()
+    );
+    assert_eq!(mint(Some(Slippage::MaxIn("100".into()))), "100".into());
+}
+
+#[test]
+fn absolute_bounds_replace_quote() {
+    let params = burn()
+        .min_amount_out("0.01")
+        .burn()
+        .unwrap()
+        .burn_params
+        .unwrap();
+    assert_eq!(params.amount_out_min, "0.01".into());
+
+    let error = burn().min_amount_out("100").burn().unwrap_err();
+    assert!(matches!(
+        error,
+        ActionError::SlippageExceeded { bound, .. } if bound == "100".into()
+    ));
+
+    let error = adapter()
+        .configure()
+        .amount_out("10")
+        .pool("0x123")
+        .asset_in("0xa")
+        .receiver("0xME")
+        .max_amount_in("1")
+        .fetch()
+        .unwrap()
+        .mint()
+        .unwrap_err();
+    assert!(matches!(error, ActionError::SlippageExceeded { .. }));
+}
+
+#[test]
+fn price_limit_bounds_amount_out() {
+    // at most 2.5 of 0xa for one 0xb, mid price is 2
+    let params = swap()
+        .price_limit("2.5")
+        .swap()
+        .unwrap()
+        .swap_params
+        .unwrap();
+    assert_eq!(params.amount_out_min, "0.8".into());
+    assert!(swap().price_limit("1.5").swap().is_err());
+    assert!(params.amount_out_min > Num::ZERO);
+}
@@ -0,0 +1,113 @@
+use crate::{
+    multipool_math::{
+        get_suppliable_burn_solution, get_utilisable_mint_solution,
+        solver::{
+            bracketed_root, burn_fee_residual, mint_fee_residual, solve_burn_numerically,
+            solve_mint_numerically, CurveBranch, CurveRoot, CurveSolution, Residual,
+        },
+    },
+    num::{num::Num, snum::SNum},
+    tests::common::{asset, context},
+};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn bracketed_root_finds_square_root() {
+    let two = Num::from("2");
+    let (a, b) = bracketed_root(
+        |x: &Num| Residual::Finite(SNum::from(x.pow2()) - two.into()),
+        Num::ZERO,
+        two,
+    )
+    .unwrap();
+    assert!(a <= b);
+    assert!(b - a <= Num::ONE);
+    assert_eq!(a.to_string()[..12], *"1.4142135623");
+}
+
+#[test]
+fn mint_solution_reports_closed_form_root() {
+    let solution =
+        get_utilisable_mint_solution("5.0051875".into(), &asset("50", "10"), &context()).unwrap();
+    assert_eq!(
+        solution,
+        Some(CurveSolution {
+            quantity: "5".into(),
+            branch: CurveBranch::AboveTarget,
+            root: CurveRoot::Plus,
+        })
+    );
+}
+
+#[test]
+fn burn_solution_reports_closed_form_root() {
+    let solution = get_suppliable_burn_solution("5".into(), &asset("50", "10"), &context())
+        .unwrap()
+        .unwrap();
+    assert_eq!(solution.branch, CurveBranch::BelowTarget);
+    assert_eq!(solution.root, CurveRoot::Minus);
+}
+
+#[test]
+fn numeric_mint_agrees_with_closed_form() {
+    let (asset, context) = (asset("50", "10"), context());
+    for supplied in ["5.0051875", "20", "24.99"] {
+        let closed = get_utilisable_mint_solution(supplied.into(), &asset, &context)
+            .unwrap()
+            .unwrap();
+        let numeric = solve_mint_numerically(supplied.into(), &asset, &context).unwrap();
+        assert_eq!(numeric.root, CurveRoot::Numeric);
+        assert_eq!(numeric.branch, closed.branch);
+        assert!(
+            closed.quantity.max(numeric.quantity) - closed.quantity.min(numeric.quantity)
+                < "0.000000000001".into()
+        );
+        // never charges more than supplied
+        assert!(
+            !mint_fee_residual(numeric.quantity, supplied.into(), &asset, &context).is_positive()
+        );
+    }
+}
+
+#[test]
+fn numeric_burn_agrees_with_closed_form() {
+    let (asset, context) = (asset("50", "10"), context());
+    for utilisable in ["5", "10", "15"] {
+        let closed = get_suppliable_burn_solution(utilisable.into(), &asset, &context)
+            .unwrap()
+            .unwrap();
+        let numeric = solve_burn_numerically(utilisable.into(), &asset, &context).unwrap();
+        assert_eq!(numeric.root, CurveRoot::Numeric);
+        assert_eq!(numeric.branch, closed.branch);
+        assert!(
+            closed.quantity.max(numeric.quantity) - closed.quantity.min(numeric.quantity)
+                < "0.000000000001".into()
+        );
+        // always covers requested quantity with fees
+        let residual = burn_fee_residual(numeric.quantity, utilisable.into(), &asset, &context);
+        assert!(residual.is_positive() || residual.is_zero());
+    }
+}
+
+#[test]
+fn numeric_burn_has_no_solution_past_limit() {
+    assert_eq!(
+        solve_burn_numerically("18".into(), &asset("50", "10"), &context()),
+        None
+    );
+    assert_eq!(
+        get_suppliable_burn_solution("18".into(), &asset("50", "10"), &context()),
+        Ok(None)
+    );
+}
+
+#[test]
+fn numeric_mint_handles_dust() {
+    let solution = get_utilisable_mint_solution(
+        "0.000000000000000001".into(),
+        &asset("50", "10"),
+        &context(),
+    )
+    .unwrap();
+    assert!(solution.is_some_and(|s| !s.quantity.is_zero()));
+}
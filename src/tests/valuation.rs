@@ -0,0 +1,81 @@
+use crate::{
+    multipool_math::{
+        pool::MpPool,
+        valuation::{AssetBacking, ValueUnit},
+        MpError, MpOperation,
+    },
+    num::num::Num,
+    tests::common,
+};
+use pretty_assertions::assert_eq;
+
+fn pool() -> MpPool {
+    MpPool {
+        total_supply: "300".into(),
+        ..common::pool()
+    }
+}
+
+#[test]
+fn share_price_rounds_down() {
+    let pool = pool();
+    // 1000 / 300 in usd, 1000 / 300 / 20 in 0xb
+    assert_eq!(
+        pool.share_price(&ValueUnit::Usd).unwrap(),
+        "3.333333333333333333333333".into()
+    );
+    assert_eq!(
+        pool.share_price(&ValueUnit::Asset("0xb".into())).unwrap(),
+        "0.166666666666666666666666".into()
+    );
+}
+
+#[test]
+fn position_value_in_asset() {
+    let pool = pool();
+    let unit = ValueUnit::Asset("0xa".into());
+    assert_eq!(
+        pool.position_value("150".into(), &unit).unwrap(),
+        "50".into()
+    );
+    assert_eq!(
+        pool.position_value("150".into(), &ValueUnit::Usd).unwrap(),
+        "500".into()
+    );
+}
+
+#[test]
+fn backing_covers_every_asset() {
+    let pool = MpPool {
+        total_supply: "100".into(),
+        ..pool()
+    };
+    let backing = pool.asset_backing_per_share(&ValueUnit::Usd).unwrap();
+    assert_eq!(
+        backing["0xa"],
+        AssetBacking {
+            quantity: "0.5".into(),
+            value: "5".into(),
+        }
+    );
+    assert_eq!(
+        backing["0xb"],
+        AssetBacking {
+            quantity: "0.25".into(),
+            value: "5".into(),
+        }
+    );
+    let total: Num = backing.values().map(|backing| backing.value).sum();
+    assert_eq!(total, pool.share_price(&ValueUnit::Usd).unwrap());
+}
+
+#[test]
+fn unknown_unit_fails() {
+    assert!(matches!(
+        pool().share_price(&ValueUnit::Asset("0xc".into())),
+        Err(MpError::UnknownAsset {
+            operation: MpOperation::Valuation,
+            ..
+        })
+    ));
+}